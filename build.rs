@@ -0,0 +1,9 @@
+use std::env;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Reported by argv::report().
+    let target = env::var("TARGET").unwrap();
+    println!("cargo:rustc-env=ARGV_TARGET={}", target);
+}
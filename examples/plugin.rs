@@ -0,0 +1,22 @@
+// A shared library for loading into another program with dlopen, which
+// reports the host program's arguments.
+
+use std::os::raw::c_char;
+
+/// Writes the host program's arguments into `buf`, each followed by a NUL
+/// byte, and returns the number of bytes needed, which may exceed `len`.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn plugin_args(buf: *mut c_char, len: usize) -> usize {
+    let mut bytes = Vec::new();
+    for arg in argv::iter() {
+        bytes.extend_from_slice(arg.to_string_lossy().as_bytes());
+        bytes.push(0);
+    }
+    let n = bytes.len().min(len);
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr().cast(), buf, n) };
+    bytes.len()
+}
@@ -0,0 +1,22 @@
+// A shared library for injecting into another program with LD_PRELOAD, which
+// prints the host program's arguments as it exits.
+//
+//     $ cargo build --example preload
+//     $ LD_PRELOAD=target/debug/examples/libpreload.so true --flag
+
+use std::io::{self, Write};
+
+#[cfg(target_os = "linux")]
+#[link_section = ".fini_array"]
+#[used]
+static REPORT: extern "C" fn() = report;
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+extern "C" fn report() {
+    let mut stderr = io::stderr().lock();
+    let _ = write!(stderr, "argv:");
+    for arg in argv::iter() {
+        let _ = write!(stderr, " {}", arg.to_string_lossy());
+    }
+    let _ = writeln!(stderr);
+}
@@ -0,0 +1,112 @@
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::iter::FromIterator;
+
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    if let Some(arg) = args.into_iter().next() {
+        return compile_error("#[argv::main] does not take arguments", arg.span());
+    }
+    match expand(input) {
+        Ok(output) => output,
+        Err((message, span)) => compile_error(message, span),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream, (&'static str, Span)> {
+    let mut tokens = Vec::from_iter(input);
+
+    let fn_index = tokens
+        .windows(2)
+        .position(|pair| is_ident(&pair[0], "fn") && is_ident(&pair[1], "main"))
+        .ok_or((
+            "#[argv::main] must be applied to `fn main`",
+            Span::call_site(),
+        ))?;
+    let name_span = tokens[fn_index + 1].span();
+    let has_param = match tokens.get(fn_index + 2) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            !group.stream().is_empty()
+        }
+        _ => return Err(("expected parameter list", name_span)),
+    };
+    let body_index = match tokens.last() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => tokens.len() - 1,
+        _ => return Err(("expected function body", name_span)),
+    };
+    let signature = tokens[..fn_index]
+        .iter()
+        .any(|token| is_ident(token, "async") || is_ident(token, "const"));
+    if signature {
+        return Err((
+            "#[argv::main] does not support async or const fn",
+            name_span,
+        ));
+    }
+
+    // fn main() -> R {
+    //     fn __argv_main(args: argv::Args) -> R { ... }
+    //     __argv_main(::argv::__private::MainArgs::main_args())
+    // }
+    let return_type = TokenStream::from_iter(tokens[fn_index + 3..body_index].iter().cloned());
+    tokens[fn_index + 1] = TokenTree::Ident(Ident::new("__argv_main", name_span));
+    let mut body = TokenStream::from_iter(tokens);
+    let call_args = if has_param {
+        path(&["argv", "__private", "MainArgs", "main_args"])
+            .into_iter()
+            .chain(Some(group(Delimiter::Parenthesis, TokenStream::new())))
+            .collect()
+    } else {
+        TokenStream::new()
+    };
+    body.extend(vec![
+        TokenTree::Ident(Ident::new("__argv_main", name_span)),
+        group(Delimiter::Parenthesis, call_args),
+    ]);
+
+    let mut output = TokenStream::from_iter(vec![
+        TokenTree::Ident(Ident::new("fn", Span::call_site())),
+        TokenTree::Ident(Ident::new("main", name_span)),
+        group(Delimiter::Parenthesis, TokenStream::new()),
+    ]);
+    output.extend(return_type);
+    output.extend(Some(group(Delimiter::Brace, body)));
+    Ok(output)
+}
+
+fn is_ident(token: &TokenTree, name: &str) -> bool {
+    match token {
+        TokenTree::Ident(ident) => ident.to_string() == name,
+        _ => false,
+    }
+}
+
+fn group(delimiter: Delimiter, stream: TokenStream) -> TokenTree {
+    TokenTree::Group(Group::new(delimiter, stream))
+}
+
+// ::a::b::c
+fn path(segments: &[&str]) -> TokenStream {
+    let mut tokens = Vec::new();
+    for segment in segments {
+        tokens.push(TokenTree::Punct(Punct::new(':', Spacing::Joint)));
+        tokens.push(TokenTree::Punct(Punct::new(':', Spacing::Alone)));
+        tokens.push(TokenTree::Ident(Ident::new(segment, Span::call_site())));
+    }
+    TokenStream::from_iter(tokens)
+}
+
+// compile_error! { "message" }
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut message = Literal::string(message);
+    message.set_span(span);
+    let mut tokens = path(&["core", "compile_error"]);
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+    let mut braces = Group::new(
+        Delimiter::Brace,
+        TokenStream::from(TokenTree::Literal(message)),
+    );
+    braces.set_span(span);
+    tokens.extend(vec![TokenTree::Punct(bang), TokenTree::Group(braces)]);
+    tokens
+}
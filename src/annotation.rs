@@ -0,0 +1,86 @@
+//! Pre-formatted command line for crash reporters.
+//!
+//! Crash reporting libraries such as Crashpad and Breakpad attach annotations
+//! to a minidump by recording a pointer and length to memory that is read
+//! when the process crashes. By the time of a crash it is too late to format
+//! anything, so this module formats the command line once at startup into a
+//! fixed-size buffer that stays valid for the rest of the program.
+//!
+//! # Example
+//!
+//! ```
+//! use argv::redact::Redactor;
+//!
+//! let annotation = argv::annotation::install(&Redactor::with_defaults());
+//! let (ptr, len) = (annotation.as_ptr(), annotation.len());
+//! // register_crash_annotation("argv", ptr, len);
+//! # let _ = (ptr, len);
+//! ```
+
+use crate::once::OnceCell;
+use crate::redact::Redactor;
+use std::fmt::{self, Write};
+
+/// Size of the static buffer used by [`install`], including a trailing NUL.
+pub const CAPACITY: usize = 4096;
+
+// The buffer, and the length of the formatted portion.
+static BUFFER: OnceCell<([u8; CAPACITY], usize)> = OnceCell::new();
+
+/// Formats the shell-quoted, redacted command line of the current process
+/// into `buf`, truncating it if necessary, and returns the number of bytes
+/// written.
+///
+/// Truncation happens at a UTF-8 character boundary.
+pub fn format_into(buf: &mut [u8], redactor: &Redactor) -> usize {
+    let mut writer = Truncate { buf, len: 0 };
+    let _ = write!(writer, "{}", redactor.display(crate::iter()));
+    writer.len
+}
+
+/// Formats the command line into a static buffer of [`CAPACITY`] bytes, once,
+/// and returns the formatted portion.
+///
+/// The byte following the returned slice is always NUL, so the pointer may
+/// also be registered as a C string. Calls after the first return the same
+/// slice and ignore their argument.
+pub fn install(redactor: &Redactor) -> &'static [u8] {
+    let (buffer, len) = BUFFER.get_or_init(|| {
+        let mut buffer = [0; CAPACITY];
+        let len = format_into(&mut buffer[..CAPACITY - 1], redactor);
+        (buffer, len)
+    });
+    &buffer[..*len]
+}
+
+/// Returns the buffer previously filled in by [`install`].
+///
+/// This only performs an atomic load and is safe to call from a signal
+/// handler.
+pub fn get() -> Option<&'static [u8]> {
+    // Unguarded for the same reason as argv::raw_iter().
+    let (buffer, len) = BUFFER.get()?;
+    Some(&buffer[..*len])
+}
+
+struct Truncate<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for Truncate<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let available = self.buf.len() - self.len;
+        let mut n = s.len().min(available);
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        if n < s.len() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Strings passed by the kernel to every process on Apple platforms.
+//!
+//! In addition to argv and envp, the XNU kernel passes a third
+//! NULL-terminated array of strings, conventionally called `apple`, to the
+//! program at startup. It holds entries such as
+//! `executable_path=/path/to/binary`, `stack_guard=0x...` and
+//! `ptr_munge=0x...`.
+//!
+//! The kernel places the array directly after the environment array, which
+//! itself directly follows the argument array. It is located from the
+//! argument array that dyld records for `_NSGetArgv`, rather than by a static
+//! constructor, because this crate's constructor stays disabled on macOS
+//! until <https://github.com/dtolnay/argv/issues/1> is resolved. The
+//! iterator is empty if the argument or environment array has been replaced
+//! by the time of the first call, for example because a variable was added
+//! to the environment.
+
+use crate::once::OnceCell;
+use std::ffi::{CStr, OsStr};
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+use std::ptr;
+
+// Address of the first entry of the array, or 0 if it could not be located.
+static APPLE: OnceCell<usize> = OnceCell::new();
+
+fn locate() -> usize {
+    extern "C" {
+        fn _NSGetArgc() -> *mut c_int;
+        fn _NSGetArgv() -> *mut *mut *mut c_char;
+        fn _NSGetEnviron() -> *mut *mut *mut c_char;
+    }
+
+    let argc = unsafe { *_NSGetArgc() };
+    let argv = unsafe { *_NSGetArgv() };
+    let envp = unsafe { *_NSGetEnviron() };
+    // Only the arrays placed by the kernel are known to be followed by
+    // another one.
+    if argc < 0 || argv.is_null() || envp != unsafe { argv.add(argc as usize + 1) } {
+        return 0;
+    }
+    let mut next = envp;
+    while !unsafe { *next }.is_null() {
+        next = unsafe { next.add(1) };
+    }
+    unsafe { next.add(1) as usize }
+}
+
+/// Returns an iterator over the `apple` strings.
+pub fn iter() -> Iter {
+    let next = *APPLE.get_or_init(locate) as *const *const c_char;
+    Iter { next }
+}
+
+/// Returns the path of the executable as reported by the kernel in the
+/// `executable_path=` entry.
+pub fn executable_path() -> Option<&'static OsStr> {
+    let prefix = b"executable_path=";
+    iter().find_map(|entry| {
+        let bytes = entry.as_bytes();
+        if bytes.starts_with(prefix) {
+            Some(OsStr::from_bytes(&bytes[prefix.len()..]))
+        } else {
+            None
+        }
+    })
+}
+
+/// Iterator over the `apple` strings.
+pub struct Iter {
+    next: *const *const c_char,
+}
+
+impl Iterator for Iter {
+    type Item = &'static OsStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let ptr = unsafe { *self.next };
+        if ptr.is_null() {
+            self.next = ptr::null();
+            return None;
+        }
+        let c_str = unsafe { CStr::from_ptr(ptr) };
+        self.next = unsafe { self.next.add(1) };
+        Some(OsStr::from_bytes(c_str.to_bytes()))
+    }
+}
+
+// Thread safe despite the raw pointers.
+unsafe impl Send for Iter {}
+unsafe impl Sync for Iter {}
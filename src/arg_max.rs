@@ -0,0 +1,98 @@
+use std::env;
+use std::ffi::OsStr;
+use std::mem;
+
+/// Returns the system's limit on the combined size of the arguments and
+/// environment passed to a new process, in bytes.
+///
+/// On Unix this is `sysconf(_SC_ARG_MAX)`. On Windows the limit applies to
+/// the command line alone, which `CreateProcess` accepts up to 32767 UTF-16
+/// code units long. Returns None on other platforms, or if the system
+/// reports no limit.
+///
+/// Linux additionally limits each individual argument to 128 KiB, which is
+/// not reflected here.
+pub fn arg_max() -> Option<usize> {
+    crate::arg_max::r#impl::arg_max()
+}
+
+/// Returns how many bytes of arguments a program started by the current
+/// process can be given, according to [`arg_cost`], assuming it inherits the
+/// current environment.
+///
+/// On Unix the environment is subtracted from [`arg_max`], as well as a
+/// further 2048 bytes of headroom as recommended by POSIX for `xargs`, which
+/// absorbs variables set on the child individually. Tools that batch
+/// arguments across several invocations of a child, like `xargs` or a build
+/// system invoking a compiler, can fill each invocation up to this budget.
+pub fn arg_budget() -> Option<usize> {
+    let arg_max = arg_max()?;
+    let env = if cfg!(unix) {
+        let strings: usize = env::vars_os()
+            .map(|(key, value)| key.len() + value.len() + 2 + mem::size_of::<usize>())
+            .sum();
+        strings + mem::size_of::<usize>() + HEADROOM
+    } else {
+        0
+    };
+    // The null pointer terminating the argument array, or the NUL
+    // terminating the command line.
+    let terminator = if cfg!(unix) {
+        mem::size_of::<usize>()
+    } else {
+        1
+    };
+    Some(arg_max.saturating_sub(env + terminator))
+}
+
+/// Returns how many bytes of the [`arg_budget`] an argument takes up.
+///
+/// On Unix this counts the string with its NUL terminator and the pointer to
+/// it in the argument array. On Windows it counts the argument's length plus
+/// a separating space, without regard to any quoting the argument may need.
+pub fn arg_cost(arg: &OsStr) -> usize {
+    if cfg!(unix) {
+        arg.len() + 1 + mem::size_of::<usize>()
+    } else {
+        arg.len() + 1
+    }
+}
+
+const HEADROOM: usize = 2048;
+
+#[cfg(unix)]
+mod r#impl {
+    use std::os::raw::{c_int, c_long};
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const _SC_ARG_MAX: c_int = 0;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const _SC_ARG_MAX: c_int = 1;
+
+    extern "C" {
+        fn sysconf(name: c_int) -> c_long;
+    }
+
+    pub(crate) fn arg_max() -> Option<usize> {
+        let arg_max = unsafe { sysconf(_SC_ARG_MAX) };
+        if arg_max > 0 {
+            Some(arg_max as usize)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(windows)]
+mod r#impl {
+    pub(crate) fn arg_max() -> Option<usize> {
+        Some(32767)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod r#impl {
+    pub(crate) fn arg_max() -> Option<usize> {
+        None
+    }
+}
@@ -0,0 +1,57 @@
+//! Parsing the command line with [argh].
+//!
+//! Requires the `argh` feature. argh only accepts arguments that are valid
+//! UTF-8, and its own `argh::from_env` reports one that is not without saying
+//! which. The function here takes the arguments from
+//! [`argv::iter()`][crate::iter], so an override installed through
+//! [`argv::mock`][crate::mock] is seen by the parser too, and names the
+//! position of any argument that fails to convert.
+//!
+//! [argh]: https://docs.rs/argh
+
+use ::argh::{EarlyExit, TopLevelCommand};
+use std::path::Path;
+use std::process;
+
+/// Parses the command line into `T`, exiting the process on error or after
+/// printing help.
+///
+/// Equivalent to `argh::from_env()`, except that the arguments come from
+/// [`argv::iter()`][crate::iter]. The command name shown in usage messages is
+/// the file name of [`argv::program()`][crate::program].
+pub fn from_env<T: TopLevelCommand>() -> T {
+    let mut strings = Vec::with_capacity(crate::iter().len());
+    for (index, arg) in crate::iter().enumerate().skip(1) {
+        match arg.to_str() {
+            Some(string) => strings.push(string),
+            None => {
+                eprintln!(
+                    "argument {} is not valid UTF-8: {:?}",
+                    index,
+                    arg.to_string_lossy(),
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    let program = crate::program();
+    let cmd = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+
+    match T::from_args(&[cmd], &strings) {
+        Ok(command) => command,
+        Err(EarlyExit { output, status }) => match status {
+            Ok(()) => {
+                println!("{}", output);
+                process::exit(0);
+            }
+            Err(()) => {
+                eprintln!("{}\nRun {} --help for more information.", output, cmd);
+                process::exit(1);
+            }
+        },
+    }
+}
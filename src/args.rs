@@ -0,0 +1,40 @@
+/// Returns an iterator over command line arguments as `&'static str`.
+///
+/// This is a replacement for `std::env::args()` that produces the arguments
+/// by reference instead of as owned strings, but behaves the same otherwise:
+/// the first item is the program name as usual, and iterating past an
+/// argument that is not valid Unicode panics. Use [`iter`][crate::iter] to
+/// handle such arguments.
+pub fn args() -> Args {
+    Args {
+        inner: crate::iter(),
+    }
+}
+
+/// Iterator over command line arguments as `&'static str`, returned by
+/// [`args`].
+#[derive(Clone)]
+pub struct Args {
+    inner: crate::Iter,
+}
+
+impl Iterator for Args {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.inner.next_str()? {
+            Ok(arg) => Some(arg),
+            Err(arg) => panic!("argument is not valid Unicode: {:?}", arg),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Args {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
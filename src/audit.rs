@@ -0,0 +1,122 @@
+//! Detecting secrets passed on the command line.
+//!
+//! Anything on the command line is visible to other users of the system
+//! through `ps`, and tends to end up in shell history and logs. Teams that
+//! enforce a "no secrets on the command line" policy can run this check at
+//! startup to warn about, or refuse, such invocations.
+//!
+//! # Example
+//!
+//! ```
+//! argv::audit::warn_on_secrets(|finding| {
+//!     eprintln!("warning: {}", finding);
+//! });
+//! ```
+
+use crate::redact::Redactor;
+use std::ffi::OsStr;
+use std::fmt::{self, Display};
+
+/// Argument that appears to contain a secret.
+#[derive(Clone, Debug)]
+pub struct Finding {
+    index: usize,
+    reason: String,
+}
+
+impl Finding {
+    /// Index of the argument containing the secret, counting `argv[0]` as 0.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Why the argument was considered a secret: the flag or variable name it
+    /// was passed to, or the kind of credential it resembles. Never contains
+    /// the secret itself.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "command line argument {} looks like a secret ({})",
+            self.index, self.reason,
+        )
+    }
+}
+
+/// Calls `callback` for every command line argument of the current process
+/// that appears to contain a secret, according to
+/// [`Redactor::with_defaults`] and a list of well-known credential formats.
+pub fn warn_on_secrets<F>(callback: F)
+where
+    F: FnMut(&Finding),
+{
+    check(&Redactor::with_defaults(), crate::iter(), callback);
+}
+
+/// Calls `callback` for every argument in `args` that matches one of the
+/// redactor's patterns or a well-known credential format.
+pub fn check<'a, I, F>(redactor: &Redactor, args: I, mut callback: F)
+where
+    I: IntoIterator<Item = &'a OsStr>,
+    F: FnMut(&Finding),
+{
+    let mut flag: Option<String> = None;
+    for (index, arg) in args.into_iter().enumerate() {
+        let lossy = arg.to_string_lossy();
+        let reason = if let Some(flag) = flag.take() {
+            Some(flag)
+        } else if let Some(kind) = credential_kind(&lossy) {
+            Some(kind.to_owned())
+        } else {
+            match lossy.find('=') {
+                Some(eq) if redactor.matches(&lossy[..eq]) => Some(lossy[..eq].to_owned()),
+                Some(eq) => credential_kind(&lossy[eq + 1..]).map(str::to_owned),
+                None => {
+                    if redactor.matches(&lossy) {
+                        flag = Some(lossy.into_owned());
+                    }
+                    None
+                }
+            }
+        };
+        if let Some(reason) = reason {
+            callback(&Finding { index, reason });
+        }
+    }
+}
+
+// Recognizes values by the fixed prefixes that credential issuers put on them.
+fn credential_kind(value: &str) -> Option<&'static str> {
+    const PREFIXES: &[(&str, &str)] = &[
+        ("ghp_", "GitHub token"),
+        ("gho_", "GitHub token"),
+        ("ghs_", "GitHub token"),
+        ("github_pat_", "GitHub token"),
+        ("glpat-", "GitLab token"),
+        ("xoxb-", "Slack token"),
+        ("xoxp-", "Slack token"),
+        ("sk_live_", "Stripe key"),
+    ];
+    for (prefix, kind) in PREFIXES {
+        if value.starts_with(prefix) {
+            return Some(kind);
+        }
+    }
+    if value.starts_with("-----BEGIN ") && value.contains("PRIVATE KEY-----") {
+        return Some("PEM private key");
+    }
+    let aws_key_id = value.len() == 20
+        && value.starts_with("AKIA")
+        && value[4..]
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+    if aws_key_id {
+        return Some("AWS access key id");
+    }
+    None
+}
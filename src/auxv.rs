@@ -0,0 +1,114 @@
+//! Values from the auxiliary vector passed by the kernel at program startup.
+//!
+//! These accessors are available on Linux and Android.
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_ulong};
+
+const AT_PAGESZ: c_ulong = 6;
+const AT_BASE: c_ulong = 7;
+const AT_UID: c_ulong = 11;
+const AT_EUID: c_ulong = 12;
+const AT_GID: c_ulong = 13;
+const AT_EGID: c_ulong = 14;
+const AT_PLATFORM: c_ulong = 15;
+const AT_SECURE: c_ulong = 23;
+const AT_EXECFN: c_ulong = 31;
+
+const ENOENT: c_int = 2;
+
+extern "C" {
+    fn getauxval(type_: c_ulong) -> c_ulong;
+    #[cfg_attr(target_os = "android", link_name = "__errno")]
+    fn __errno_location() -> *mut c_int;
+}
+
+/// Looks up an entry of the auxiliary vector by its `AT_*` type number.
+///
+/// Returns None if the kernel did not provide an entry of that type. Under
+/// Miri, which does not model the auxiliary vector, this is always None.
+pub fn get(type_: c_ulong) -> Option<c_ulong> {
+    let _guard = crate::zero_alloc::enter();
+    if cfg!(miri) {
+        return None;
+    }
+    unsafe {
+        let errno = __errno_location();
+        *errno = 0;
+        let value = getauxval(type_);
+        if value == 0 && *errno == ENOENT {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+/// System page size in bytes (`AT_PAGESZ`).
+pub fn page_size() -> Option<usize> {
+    get(AT_PAGESZ).map(|value| value as usize)
+}
+
+/// Base address at which the program interpreter (dynamic linker) was loaded
+/// (`AT_BASE`).
+///
+/// This is 0 for statically linked programs.
+pub fn base() -> Option<usize> {
+    get(AT_BASE).map(|value| value as usize)
+}
+
+/// Real user id of the process at startup (`AT_UID`).
+pub fn uid() -> Option<u32> {
+    get(AT_UID).map(|value| value as u32)
+}
+
+/// Effective user id of the process at startup (`AT_EUID`).
+pub fn euid() -> Option<u32> {
+    get(AT_EUID).map(|value| value as u32)
+}
+
+/// Real group id of the process at startup (`AT_GID`).
+pub fn gid() -> Option<u32> {
+    get(AT_GID).map(|value| value as u32)
+}
+
+/// Effective group id of the process at startup (`AT_EGID`).
+pub fn egid() -> Option<u32> {
+    get(AT_EGID).map(|value| value as u32)
+}
+
+/// String identifying the hardware platform, such as `"x86_64"`
+/// (`AT_PLATFORM`).
+///
+/// The kernel places this string in the initial process stack, where it lives
+/// for the duration of the program.
+pub fn platform() -> Option<&'static CStr> {
+    let ptr = get(AT_PLATFORM)? as *const c_char;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(ptr) })
+    }
+}
+
+/// Pathname used to execute the program (`AT_EXECFN`).
+///
+/// This is the path passed to `execve(2)`, which is usually but not
+/// necessarily the same as `argv[0]`. Like [`platform`], it lives in the
+/// initial process stack.
+pub fn execfn() -> Option<&'static CStr> {
+    let ptr = get(AT_EXECFN)? as *const c_char;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(ptr) })
+    }
+}
+
+/// Whether the kernel flagged this process for secure-execution mode
+/// (`AT_SECURE`).
+///
+/// See [`is_secure_exec`][crate::is_secure_exec].
+pub fn secure() -> bool {
+    get(AT_SECURE).map_or(false, |value| value != 0)
+}
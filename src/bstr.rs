@@ -0,0 +1,70 @@
+//! Command line arguments as [bstr] byte strings.
+//!
+//! Requires the `bstr` feature.
+//!
+//! [bstr]: https://docs.rs/bstr
+
+use ::bstr::BStr;
+use std::ffi::OsStr;
+
+/// Returns an iterator over command line arguments as `&'static BStr`.
+///
+/// On Unix each argument is produced by reference to its bytes, without
+/// copying. On other platforms, arguments that are valid Unicode are likewise
+/// produced by reference to their UTF-8 bytes, while ill-formed ones are
+/// converted lossily and the converted copy is leaked.
+///
+/// # Example
+///
+/// ```
+/// use bstr::ByteSlice;
+///
+/// let has_define = argv::bstr::iter().any(|arg| arg.starts_with_str("-D"));
+/// # let _ = has_define;
+/// ```
+pub fn iter() -> Iter {
+    Iter {
+        inner: crate::iter(),
+    }
+}
+
+/// Iterator over command line arguments as `&'static BStr`, returned by
+/// [`iter`].
+pub struct Iter {
+    inner: crate::Iter,
+}
+
+impl Iterator for Iter {
+    type Item = &'static BStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(to_bstr)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(unix)]
+fn to_bstr(arg: &'static OsStr) -> &'static BStr {
+    use std::os::unix::ffi::OsStrExt;
+
+    BStr::new(arg.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn to_bstr(arg: &'static OsStr) -> &'static BStr {
+    match arg.to_str() {
+        Some(string) => BStr::new(string),
+        None => BStr::new(Box::leak(
+            arg.to_string_lossy().into_owned().into_boxed_str(),
+        )),
+    }
+}
@@ -0,0 +1,218 @@
+//! Command line arguments as bytes, on every platform.
+//!
+//! On Unix an `OsStr` is an arbitrary byte string and the bytes are borrowed
+//! as they are. On Windows an `OsStr` is a sequence of UTF-16 code units,
+//! possibly ill-formed, which is converted to [WTF-8]: the same as UTF-8 for
+//! well-formed text, with unpaired surrogates encoded like any other code
+//! point. Either way [`to_os_str`] reverses the conversion exactly, so
+//! serialization layers can round-trip arguments through bytes without
+//! platform-specific code.
+//!
+//! [WTF-8]: https://simonsapin.github.io/wtf-8/
+//!
+//! # Example
+//!
+//! ```
+//! for (arg, bytes) in argv::iter().zip(argv::bytes::iter()) {
+//!     assert_eq!(argv::bytes::to_os_str(&bytes).unwrap(), arg);
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
+/// Returns an iterator over command line arguments as bytes.
+///
+/// Each argument is borrowed, except on Windows if it is not valid Unicode.
+pub fn iter() -> Iter {
+    Iter {
+        inner: crate::iter(),
+    }
+}
+
+/// Iterator over command line arguments as bytes, returned by [`iter`].
+#[derive(Clone)]
+pub struct Iter {
+    inner: crate::Iter,
+}
+
+impl Iterator for Iter {
+    type Item = Cow<'static, [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(to_bytes)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// Returns an iterator over command line arguments in the encoding `OsStr`
+/// uses internally, as `&'static [u8]`.
+///
+/// This is the same as [`bytes::iter`][iter], except that the bytes of an
+/// argument that needs converting, which is only the case on Windows for
+/// arguments that are not valid Unicode, are leaked instead of owned, so
+/// every argument can be produced with a `'static` lifetime. The bytes are
+/// the arguments' raw bytes on Unix and their WTF-8 on Windows, making them
+/// suitable for hashing or caching identically across platforms.
+pub fn encoded_bytes() -> EncodedBytes {
+    EncodedBytes {
+        inner: crate::iter(),
+    }
+}
+
+/// Iterator over command line arguments as `&'static [u8]`, returned by
+/// [`encoded_bytes`].
+#[derive(Clone)]
+pub struct EncodedBytes {
+    inner: crate::Iter,
+}
+
+impl Iterator for EncodedBytes {
+    type Item = &'static [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arg = self.inner.next()?;
+        Some(match to_bytes(arg) {
+            Cow::Borrowed(bytes) => bytes,
+            Cow::Owned(bytes) => Box::leak(bytes.into_boxed_slice()),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for EncodedBytes {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// Converts an argument to bytes as [`iter`] does.
+#[cfg(unix)]
+pub fn to_bytes(arg: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Cow::Borrowed(arg.as_bytes())
+}
+
+/// Converts an argument to bytes as [`iter`] does.
+#[cfg(windows)]
+pub fn to_bytes(arg: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::windows::ffi::OsStrExt;
+
+    if let Some(string) = arg.to_str() {
+        return Cow::Borrowed(string.as_bytes());
+    }
+    let mut bytes = Vec::with_capacity(arg.len());
+    for unit in std::char::decode_utf16(arg.encode_wide()) {
+        match unit {
+            Ok(ch) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            }
+            Err(err) => {
+                let surrogate = err.unpaired_surrogate();
+                bytes.push(0xE0 | (surrogate >> 12) as u8);
+                bytes.push(0x80 | (surrogate >> 6 & 0x3F) as u8);
+                bytes.push(0x80 | (surrogate & 0x3F) as u8);
+            }
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+/// Converts an argument to bytes as [`iter`] does.
+#[cfg(not(any(unix, windows)))]
+pub fn to_bytes(arg: &OsStr) -> Cow<'_, [u8]> {
+    match arg.to_string_lossy() {
+        Cow::Borrowed(string) => Cow::Borrowed(string.as_bytes()),
+        Cow::Owned(string) => Cow::Owned(string.into_bytes()),
+    }
+}
+
+/// Converts bytes produced by [`iter`] or [`to_bytes`] back to the argument.
+///
+/// On Unix this borrows and never fails. On Windows it returns None if the
+/// bytes are not WTF-8. On other platforms it returns None if the bytes are
+/// not UTF-8.
+#[cfg(unix)]
+pub fn to_os_str(bytes: &[u8]) -> Option<Cow<'_, OsStr>> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Some(Cow::Borrowed(OsStr::from_bytes(bytes)))
+}
+
+/// Converts bytes produced by [`iter`] or [`to_bytes`] back to the argument.
+///
+/// On Unix this borrows and never fails. On Windows it returns None if the
+/// bytes are not WTF-8. On other platforms it returns None if the bytes are
+/// not UTF-8.
+#[cfg(windows)]
+pub fn to_os_str(bytes: &[u8]) -> Option<Cow<'_, OsStr>> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+
+    if let Ok(string) = std::str::from_utf8(bytes) {
+        return Some(Cow::Borrowed(OsStr::new(string)));
+    }
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let first = bytes[i];
+        let (len, min, init) = match first {
+            0x00..=0x7F => (1, 0, u32::from(first)),
+            0xC0..=0xDF => (2, 0x80, u32::from(first & 0x1F)),
+            0xE0..=0xEF => (3, 0x800, u32::from(first & 0x0F)),
+            0xF0..=0xF7 => (4, 0x10000, u32::from(first & 0x07)),
+            _ => return None,
+        };
+        let rest = bytes.get(i + 1..i + len)?;
+        let mut code_point = init;
+        for &byte in rest {
+            if byte & 0xC0 != 0x80 {
+                return None;
+            }
+            code_point = code_point << 6 | u32::from(byte & 0x3F);
+        }
+        if code_point < min || code_point > 0x10FFFF {
+            return None;
+        }
+        // WTF-8 encodes a surrogate pair as one 4-byte sequence, never as two
+        // 3-byte surrogates as in CESU-8.
+        let lead = units.last().map_or(false, |&unit| unit & 0xFC00 == 0xD800);
+        if lead && code_point & 0xFC00 == 0xDC00 {
+            return None;
+        }
+        if code_point >= 0x10000 {
+            let offset = code_point - 0x10000;
+            units.push(0xD800 | (offset >> 10) as u16);
+            units.push(0xDC00 | (offset & 0x3FF) as u16);
+        } else {
+            units.push(code_point as u16);
+        }
+        i += len;
+    }
+    Some(Cow::Owned(OsString::from_wide(&units)))
+}
+
+/// Converts bytes produced by [`iter`] or [`to_bytes`] back to the argument.
+///
+/// On Unix this borrows and never fails. On Windows it returns None if the
+/// bytes are not WTF-8. On other platforms it returns None if the bytes are
+/// not UTF-8.
+#[cfg(not(any(unix, windows)))]
+pub fn to_os_str(bytes: &[u8]) -> Option<Cow<'_, OsStr>> {
+    let string = std::str::from_utf8(bytes).ok()?;
+    Some(Cow::Borrowed(OsStr::new(string)))
+}
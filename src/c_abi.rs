@@ -0,0 +1,49 @@
+// Functions with C linkage exposing the command line to C code linked into
+// the same binary, enabled by the `c-abi` feature. Declared in C as:
+//
+//     int rust_argv_argc(void);
+//     char *const *rust_argv_argv(void);
+//
+// The linker only pulls these in from the rlib if the binary also uses this
+// crate from Rust.
+
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+struct Empty([*const c_char; 1]);
+
+// Never written.
+unsafe impl Sync for Empty {}
+
+static EMPTY: Empty = Empty([ptr::null()]);
+
+/// Returns the number of command line arguments, to C code.
+///
+/// Requires the `c-abi` feature. Override arguments installed through
+/// [`argv::mock`][crate::mock] are not reflected.
+#[no_mangle]
+pub extern "C" fn rust_argv_argc() -> c_int {
+    let _guard = crate::zero_alloc::enter();
+    crate::r#impl::c_argv().0
+}
+
+/// Returns the NULL-terminated array of command line arguments, to C code.
+///
+/// Requires the `c-abi` feature. The array holds [`rust_argv_argc`] strings
+/// followed by a null pointer, and remains valid for the rest of the program.
+/// C code may only modify it the way `gtk_init` does, by moving the pointers
+/// it holds and ending it early with a null pointer, after which Rust code
+/// must call [`argv::refresh()`][crate::refresh] before anything iterates
+/// over the arguments again. As with [`argv::iter()`][crate::iter], calling
+/// this prevents the memory the kernel placed the arguments in from being
+/// overwritten afterward by [`title::set`][crate::title::set].
+#[no_mangle]
+pub extern "C" fn rust_argv_argv() -> *const *const c_char {
+    let _guard = crate::zero_alloc::enter();
+    let argv = crate::r#impl::c_argv().1;
+    if argv.is_null() {
+        EMPTY.0.as_ptr()
+    } else {
+        argv
+    }
+}
@@ -0,0 +1,282 @@
+use std::ffi::{CStr, CString, OsStr};
+use std::io;
+use std::os::raw::c_char;
+use std::{mem, ptr};
+
+/// Returns an iterator over command line arguments as NUL-terminated C
+/// strings.
+///
+/// This is for passing arguments to C functions without allocating a
+/// `CString` for each one. On Linux with glibc the strings are the ones the
+/// arguments were captured from, which already end in a NUL terminator, so
+/// nothing is copied. Elsewhere the arguments are copied into NUL-terminated
+/// strings once, the first time this is called, and the copies are reused
+/// afterward. On Windows the copies are UTF-8, with ill-formed UTF-16
+/// replaced by U+FFFD REPLACEMENT CHARACTER.
+///
+/// Override arguments installed through [`argv::mock`][crate::mock] are
+/// copied every time this is called, and must not contain a NUL byte.
+///
+/// # Example
+///
+/// ```
+/// use std::os::raw::c_char;
+///
+/// # unsafe extern "C" fn puts(_: *const c_char) {}
+/// for arg in argv::c_strs() {
+///     unsafe { puts(arg.as_ptr()) };
+/// }
+/// ```
+pub fn c_strs() -> CStrs {
+    let iter = crate::iter();
+    let inner = if iter.is_native() {
+        let (argc, argv) = crate::r#impl::c_argv();
+        let end = if argv.is_null() {
+            argv
+        } else {
+            unsafe { argv.add(argc as usize) }
+        };
+        Inner::Native { next: argv, end }
+    } else {
+        Inner::Copy(iter)
+    };
+    CStrs { inner }
+}
+
+/// Iterator over command line arguments as `&'static CStr`, returned by
+/// [`c_strs`].
+#[derive(Clone)]
+pub struct CStrs {
+    inner: Inner,
+}
+
+#[derive(Clone)]
+enum Inner {
+    // Read through raw pointers, because C code may rearrange the array
+    // before calling argv::refresh().
+    Native {
+        next: *const *const c_char,
+        end: *const *const c_char,
+    },
+    Copy(crate::Iter),
+}
+
+impl Iterator for CStrs {
+    type Item = &'static CStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Native { next, end } => {
+                if next == end {
+                    return None;
+                }
+                let ptr = unsafe { **next };
+                *next = unsafe { next.add(1) };
+                // Past the end of an array shortened by C code, as by the
+                // native argv::Iter.
+                if ptr.is_null() {
+                    return Some(unsafe { CStr::from_ptr(b"\0".as_ptr().cast()) });
+                }
+                Some(unsafe { CStr::from_ptr(ptr) })
+            }
+            Inner::Copy(iter) => iter.next().map(leak),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            Inner::Native { .. } => {
+                let len = self.len();
+                (len, Some(len))
+            }
+            Inner::Copy(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl ExactSizeIterator for CStrs {
+    fn len(&self) -> usize {
+        match &self.inner {
+            Inner::Native { next, end } => {
+                (*end as usize - *next as usize) / mem::size_of::<*const c_char>()
+            }
+            Inner::Copy(iter) => iter.len(),
+        }
+    }
+}
+
+// The strings are never freed, and the array is only modified by C code
+// before argv::refresh(), which is required not to overlap with iteration.
+unsafe impl Send for CStrs {}
+unsafe impl Sync for CStrs {}
+
+fn leak(arg: &'static OsStr) -> &'static CStr {
+    let c_string = CString::new(bytes(arg)).expect("argument contains a NUL byte");
+    Box::leak(c_string.into_boxed_c_str())
+}
+
+fn bytes(arg: &OsStr) -> Vec<u8> {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(arg).to_vec();
+    #[cfg(not(unix))]
+    let bytes = arg.to_string_lossy().into_owned().into_bytes();
+    bytes
+}
+
+/// Builds a NULL-terminated array of C strings from the given arguments,
+/// laid out the way `execv` and `posix_spawn` expect, and leaks it.
+///
+/// The strings are copied into a single allocation, followed by a second one
+/// for the array of pointers to them. Both live for the rest of the program,
+/// so this is meant to be called once, for example right before replacing
+/// the process. On Windows the strings are UTF-8, with ill-formed UTF-16
+/// replaced by U+FFFD REPLACEMENT CHARACTER.
+///
+/// # Errors
+///
+/// Fails without leaking anything if an argument contains a NUL byte.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(unix)]
+/// # fn main() {
+/// use std::os::raw::{c_char, c_int};
+///
+/// extern "C" {
+///     fn execv(path: *const c_char, argv: *const *const c_char) -> c_int;
+/// }
+///
+/// let argv = argv::leak_args(&["sh", "-c", "echo hello"]).unwrap();
+/// unsafe { execv(b"/bin/sh\0".as_ptr().cast(), argv) };
+/// # }
+/// #
+/// # #[cfg(not(unix))]
+/// # fn main() {}
+/// ```
+pub fn leak_args<I>(args: I) -> io::Result<*const *const c_char>
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let mut buffer = Vec::new();
+    let mut offsets = Vec::new();
+    for arg in args {
+        let bytes = bytes(arg.as_ref());
+        if bytes.contains(&0) {
+            return Err(crate::error::new(
+                io::ErrorKind::InvalidInput,
+                "argument contains a NUL byte",
+            ));
+        }
+        offsets.push(buffer.len());
+        buffer.extend_from_slice(&bytes);
+        buffer.push(0);
+    }
+    let buffer: &'static [u8] = Box::leak(buffer.into_boxed_slice());
+    let mut argv: Vec<*const c_char> = offsets
+        .into_iter()
+        .map(|offset| buffer[offset..].as_ptr().cast())
+        .collect();
+    argv.push(ptr::null());
+    Ok(Box::leak(argv.into_boxed_slice()).as_mut_ptr())
+}
+
+/// Returns the command line arguments as a NULL-terminated array of C
+/// strings, ready to pass to `execv` or `posix_spawn`.
+///
+/// On Linux with glibc this is the argument array the process was started
+/// with, so nothing is allocated, and C code that removes arguments from it
+/// must be followed by a call to [`argv::refresh`][crate::refresh]. Elsewhere the array is built the first
+/// time the arguments are needed as C strings, as described for [`c_strs`],
+/// and reused afterward. The array and the strings live for the rest of the
+/// program. If no arguments were captured, the array is empty, consisting of
+/// just the terminating null pointer.
+///
+/// Override arguments installed through [`argv::mock`][crate::mock] are
+/// copied into a new array every time this is called, and must not contain a
+/// NUL byte.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(unix)]
+/// # fn main() {
+/// use std::os::raw::{c_char, c_int};
+///
+/// extern "C" {
+///     fn execv(path: *const c_char, argv: *const *const c_char) -> c_int;
+/// }
+///
+/// // Run a newer build of this program, with the same arguments.
+/// unsafe { execv(b"/usr/local/bin/tool\0".as_ptr().cast(), argv::as_exec_array()) };
+/// # }
+/// #
+/// # #[cfg(not(unix))]
+/// # fn main() {}
+/// ```
+pub fn as_exec_array() -> *const *const c_char {
+    struct Empty([*const c_char; 1]);
+    unsafe impl Sync for Empty {}
+    static EMPTY: Empty = Empty([ptr::null()]);
+
+    if !crate::iter().is_native() {
+        return leak_args(crate::iter()).expect("argument contains a NUL byte");
+    }
+    let (_argc, argv) = crate::r#impl::c_argv();
+    if argv.is_null() {
+        EMPTY.0.as_ptr()
+    } else {
+        argv
+    }
+}
+
+/// Returns an iterator over pointers to the command line arguments as C
+/// strings.
+///
+/// Each pointer refers to a NUL-terminated string that lives for the rest of
+/// the program, exactly as produced by [`c_strs`]. This suits building the
+/// argument array for `execv` or `posix_spawn`, or passing arguments to a
+/// variadic C function, without a round trip through `CString`.
+///
+/// # Example
+///
+/// ```
+/// use std::os::raw::c_char;
+/// use std::ptr;
+///
+/// let mut argv: Vec<*const c_char> = argv::iter_c_ptrs().collect();
+/// argv.push(ptr::null());
+/// ```
+#[cfg(unix)]
+pub fn iter_c_ptrs() -> CPtrs {
+    CPtrs { inner: c_strs() }
+}
+
+/// Iterator over pointers to NUL-terminated command line arguments, returned
+/// by [`iter_c_ptrs`].
+#[cfg(unix)]
+#[derive(Clone)]
+pub struct CPtrs {
+    inner: CStrs,
+}
+
+#[cfg(unix)]
+impl Iterator for CPtrs {
+    type Item = *const c_char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(CStr::as_ptr)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(unix)]
+impl ExactSizeIterator for CPtrs {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
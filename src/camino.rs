@@ -0,0 +1,89 @@
+//! Command line arguments as [camino] UTF-8 paths.
+//!
+//! Requires the `camino` feature.
+//!
+//! [camino]: https://docs.rs/camino
+
+use ::camino::Utf8Path;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt::{self, Display};
+use std::path::Path;
+
+/// Returns an iterator over command line arguments as `&'static Utf8Path`.
+///
+/// Arguments that are valid UTF-8 are produced by reference, without copying.
+/// Any other argument produces an error identifying its position.
+///
+/// # Example
+///
+/// ```
+/// for path in argv::camino::utf8_paths().skip(1) {
+///     match path {
+///         Ok(path) => println!("{}", path),
+///         Err(err) => eprintln!("{}", err),
+///     }
+/// }
+/// ```
+pub fn utf8_paths() -> Utf8Paths {
+    Utf8Paths {
+        inner: crate::iter().enumerate(),
+    }
+}
+
+/// Iterator over command line arguments as `&'static Utf8Path`, returned by
+/// [`utf8_paths`].
+pub struct Utf8Paths {
+    inner: std::iter::Enumerate<crate::Iter>,
+}
+
+impl Iterator for Utf8Paths {
+    type Item = Result<&'static Utf8Path, NonUtf8Arg>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, arg) = self.inner.next()?;
+        Some(Utf8Path::from_path(Path::new(arg)).ok_or(NonUtf8Arg { index, arg }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Utf8Paths {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// Error produced by [`Utf8Paths`] for an argument that is not valid UTF-8.
+#[derive(Copy, Clone, Debug)]
+pub struct NonUtf8Arg {
+    index: usize,
+    arg: &'static OsStr,
+}
+
+impl NonUtf8Arg {
+    /// Position of the argument in [`argv::iter()`][crate::iter].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The argument.
+    pub fn arg(&self) -> &'static OsStr {
+        self.arg
+    }
+}
+
+impl Display for NonUtf8Arg {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "argument {} is not valid UTF-8: {:?}",
+            self.index,
+            self.arg.to_string_lossy(),
+        )
+    }
+}
+
+impl Error for NonUtf8Arg {}
@@ -0,0 +1,39 @@
+//! Parsing the command line with [clap].
+//!
+//! Requires the `clap` feature. The functions here hand clap the
+//! `&'static OsStr` arguments produced by [`argv::iter()`][crate::iter], so an
+//! override installed through [`argv::mock`][crate::mock] is seen by the
+//! parser too.
+//!
+//! [`Iter`][crate::Iter] itself satisfies the bound of clap's
+//! `Parser::parse_from` and `Command::get_matches_from`, which take
+//! `IntoIterator<Item: Into<OsString> + Clone>`. Cloning an item copies a
+//! reference, not the string it points to, so `argv::iter()` can be passed
+//! directly wherever clap accepts an argument list.
+//!
+//! [clap]: https://docs.rs/clap
+
+use ::clap::{Error, Parser};
+
+/// Parses the command line into `T`, exiting the process with clap's usage
+/// message on error.
+///
+/// Equivalent to `T::parse()`, except that the arguments come from
+/// [`argv::iter()`][crate::iter].
+pub fn parse<T: Parser>() -> T {
+    T::parse_from(crate::iter())
+}
+
+/// Parses the command line into `T`, returning clap's error instead of
+/// exiting.
+///
+/// Equivalent to `T::try_parse()`, except that the arguments come from
+/// [`argv::iter()`][crate::iter].
+///
+/// # Errors
+///
+/// Fails if the arguments do not match `T`, or if the user asked for `--help`
+/// or `--version`.
+pub fn try_parse<T: Parser>() -> Result<T, Error> {
+    T::try_parse_from(crate::iter())
+}
@@ -0,0 +1,50 @@
+use std::io;
+use std::process::Command;
+
+/// Returns a `Command` that runs the current executable with the current
+/// arguments.
+///
+/// The program is the path located by
+/// [`current_exe_static`][crate::current_exe_static] and the arguments are
+/// the ones produced by [`iter`][crate::iter] after `argv[0]`. On Unix the
+/// child's `argv[0]` is set to the current one as well. The command can be
+/// adjusted further before spawning, for example to add arguments that tell
+/// the copy it is a worker.
+///
+/// # Errors
+///
+/// Fails if the path of the current executable could not be determined.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// let child = argv::command()?.arg("--worker").spawn()?;
+/// # let _ = child;
+/// # Ok(())
+/// # }
+/// ```
+pub fn command() -> io::Result<Command> {
+    let exe = crate::current_exe_static().ok_or_else(|| {
+        crate::error::new(
+            io::ErrorKind::NotFound,
+            "current executable could not be located",
+        )
+    })?;
+    let mut args = crate::iter();
+    let mut command = Command::new(exe);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        if let Some(arg0) = args.next() {
+            command.arg0(arg0);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = args.next();
+    }
+    command.args(args);
+    Ok(command)
+}
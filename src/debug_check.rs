@@ -0,0 +1,59 @@
+// Differential check of the captured arguments against std::env::args_os,
+// enabled by the `debug-check` feature in builds with debug assertions.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Write};
+use std::sync::Once;
+
+pub(crate) struct Mismatch {
+    captured: Vec<OsString>,
+    expected: Vec<OsString>,
+}
+
+// Compares the arguments this crate captured against the standard library's,
+// the first time it is called. Arguments captured as an empty string match
+// anything, because take_secret() leaves one of those in place of the secret.
+pub(crate) fn compare<'a>(captured: impl Iterator<Item = &'a OsStr>) -> Option<Mismatch> {
+    static ONCE: Once = Once::new();
+
+    if !cfg!(debug_assertions) || env::var_os("ARGV_DEBUG_CHECK").map_or(false, |v| v == "off") {
+        return None;
+    }
+
+    let mut mismatch = None;
+    ONCE.call_once(|| {
+        mismatch = crate::zero_alloc::exempt(|| {
+            let captured: Vec<OsString> = captured.map(OsString::from).collect();
+            let expected: Vec<OsString> = env::args_os().collect();
+            let matches = captured.len() == expected.len()
+                && captured
+                    .iter()
+                    .zip(&expected)
+                    .all(|(captured, expected)| captured.is_empty() || captured == expected);
+            if matches {
+                None
+            } else {
+                Some(Mismatch { captured, expected })
+            }
+        });
+    });
+    mismatch
+}
+
+impl Mismatch {
+    // Panics if ARGV_DEBUG_CHECK=panic, otherwise prints to stderr. Must be
+    // called without holding any of this crate's locks.
+    pub(crate) fn report(self) {
+        let message = crate::zero_alloc::exempt(|| {
+            format!(
+                "argv: captured arguments differ from std::env::args_os()\n  captured: {:?}\n  args_os:  {:?}",
+                self.captured, self.expected,
+            )
+        });
+        if env::var_os("ARGV_DEBUG_CHECK").map_or(false, |v| v == "panic") {
+            panic!("{}", message);
+        }
+        let _ = writeln!(io::stderr(), "{}", message);
+    }
+}
@@ -0,0 +1,103 @@
+use std::ffi::OsStr;
+use std::fmt::{self, Write};
+
+/// Returns an object that displays a command line argument for inclusion in
+/// a message meant for the user, such as an error message.
+///
+/// Printable characters are written unchanged. Everything that could garble
+/// the message or a terminal, or that would otherwise be lost, is escaped
+/// using Rust's notation: `\t`, `\r` and `\n`; `\\` for a backslash; `\u{1b}`
+/// for other control characters; `\xff` for bytes that are not valid UTF-8 on
+/// Unix; and `\u{d800}` for unpaired surrogates on Windows. Different
+/// arguments therefore never display the same way, unlike with
+/// `OsStr::to_string_lossy`.
+///
+/// # Example
+///
+/// ```
+/// if let Some(arg) = argv::iter().nth(1) {
+///     eprintln!("error: no such file: {}", argv::display(arg));
+/// }
+/// ```
+pub fn display(arg: &OsStr) -> ArgDisplay<'_> {
+    ArgDisplay { arg }
+}
+
+/// Helper for printing a command line argument with escapes, returned by
+/// [`display`].
+#[derive(Copy, Clone)]
+pub struct ArgDisplay<'a> {
+    arg: &'a OsStr,
+}
+
+impl fmt::Display for ArgDisplay<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write_escaped(self.arg, formatter)
+    }
+}
+
+impl fmt::Debug for ArgDisplay<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.arg, formatter)
+    }
+}
+
+fn write_char(ch: char, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match ch {
+        '\t' => formatter.write_str("\\t"),
+        '\r' => formatter.write_str("\\r"),
+        '\n' => formatter.write_str("\\n"),
+        '\\' => formatter.write_str("\\\\"),
+        _ if ch.is_control() => write!(formatter, "\\u{{{:x}}}", ch as u32),
+        _ => formatter.write_char(ch),
+    }
+}
+
+#[cfg(unix)]
+fn write_escaped(arg: &OsStr, formatter: &mut fmt::Formatter) -> fmt::Result {
+    use std::os::unix::ffi::OsStrExt;
+    use std::str;
+
+    let mut rest = arg.as_bytes();
+    loop {
+        let (valid, invalid) = match str::from_utf8(rest) {
+            Ok(valid) => (valid, &[][..]),
+            Err(err) => {
+                let (valid, after) = rest.split_at(err.valid_up_to());
+                let invalid = err.error_len().unwrap_or(after.len());
+                rest = &after[invalid..];
+                (str::from_utf8(valid).unwrap(), &after[..invalid])
+            }
+        };
+        for ch in valid.chars() {
+            write_char(ch, formatter)?;
+        }
+        if invalid.is_empty() {
+            return Ok(());
+        }
+        for byte in invalid {
+            write!(formatter, "\\x{:02x}", byte)?;
+        }
+    }
+}
+
+#[cfg(windows)]
+fn write_escaped(arg: &OsStr, formatter: &mut fmt::Formatter) -> fmt::Result {
+    use std::os::windows::ffi::OsStrExt;
+
+    for ch in std::char::decode_utf16(arg.encode_wide()) {
+        match ch {
+            Ok(ch) => write_char(ch, formatter)?,
+            Err(err) => write!(formatter, "\\u{{{:x}}}", err.unpaired_surrogate())?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn write_escaped(arg: &OsStr, formatter: &mut fmt::Formatter) -> fmt::Result {
+    for ch in arg.to_string_lossy().chars() {
+        write_char(ch, formatter)?;
+    }
+    Ok(())
+}
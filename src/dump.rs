@@ -0,0 +1,49 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// Writes a diagnostic description of every command line argument, one per
+/// line, for pasting into a bug report.
+///
+/// Each line has the index of the argument, its length in bytes, and its text
+/// converted lossily and quoted with escapes. Arguments that are not valid
+/// UTF-8 are additionally written in hexadecimal, so that the exact bytes can
+/// be recovered. On Windows the bytes are the [WTF-8] encoding of the
+/// argument, as produced by [`argv::bytes`][crate::bytes].
+///
+/// ```text
+/// argv[0]: 12 bytes, "/usr/bin/cat"
+/// argv[1]: 8 bytes, "caf�.txt", not UTF-8: 63 61 66 e9 2e 74 78 74
+/// ```
+///
+/// [WTF-8]: https://simonsapin.github.io/wtf-8/
+///
+/// # Example
+///
+/// ```
+/// if argv::iter().any(|arg| arg == "--dump-args") {
+///     argv::debug_dump(std::io::stderr()).unwrap();
+///     # if false {
+///     std::process::exit(0);
+///     # }
+/// }
+/// ```
+pub fn debug_dump<W: Write>(mut writer: W) -> io::Result<()> {
+    for (index, bytes) in crate::bytes::iter().enumerate() {
+        let lossy = String::from_utf8_lossy(&bytes);
+        write!(
+            writer,
+            "argv[{}]: {} bytes, {:?}",
+            index,
+            bytes.len(),
+            lossy
+        )?;
+        if let Cow::Owned(_) = lossy {
+            writer.write_all(b", not UTF-8:")?;
+            for byte in bytes.iter() {
+                write!(writer, " {:02x}", byte)?;
+            }
+        }
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
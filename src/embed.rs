@@ -0,0 +1,64 @@
+//! Installing the logical command line from a host application.
+//!
+//! When this crate is linked into a shared library loaded by a JVM, CPython
+//! or a plugin host, the command line of the process is the host's, such as
+//! `java -jar app.jar` or `python script.py`, rather than the arguments meant
+//! for the Rust code. The bridge between the host and Rust calls
+//! [`set_logical_args`] once with the arguments the host received on the
+//! library's behalf, after which [`argv::iter()`][crate::iter] and everything
+//! built on it produce those instead.
+//!
+//! ```
+//! // In the JNI or PyO3 entry point, with arguments obtained from the host:
+//! let args = ["app.jar", "--port", "8080"];
+//! argv::embed::set_logical_args(&args).unwrap();
+//!
+//! assert_eq!(argv::iter().nth(1).unwrap(), "--port");
+//! ```
+//!
+//! An override installed through [`argv::mock`][crate::mock] takes precedence
+//! over the logical arguments. [`argv::raw_iter()`][crate::raw_iter] and the
+//! functions that operate on the memory the kernel placed the arguments in
+//! continue to see the host's command line.
+
+use crate::once::OnceCell;
+use std::ffi::OsStr;
+use std::io;
+
+static LOGICAL: OnceCell<Vec<&OsStr>> = OnceCell::new();
+
+/// Makes `argv::iter()` produce `args` instead of the process's command line
+/// for the rest of the program.
+///
+/// The first element of `args` takes the place of `argv[0]`, and is what
+/// [`argv::program()`][crate::program] returns.
+///
+/// # Errors
+///
+/// Fails if logical arguments have already been installed. They can be set
+/// only once, because references to them may have been handed out.
+pub fn set_logical_args<I>(args: I) -> io::Result<()>
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let mut installed = false;
+    LOGICAL.get_or_init(|| {
+        installed = true;
+        args.into_iter()
+            .map(|arg| -> &OsStr { Box::leak(Box::from(arg.as_ref())) })
+            .collect()
+    });
+    if installed {
+        Ok(())
+    } else {
+        Err(crate::error::new(
+            io::ErrorKind::AlreadyExists,
+            "logical arguments have already been set",
+        ))
+    }
+}
+
+pub(crate) fn current() -> Option<&'static [&'static OsStr]> {
+    LOGICAL.get().map(Vec::as_slice)
+}
@@ -0,0 +1,76 @@
+//! Decoding arguments passed in a legacy encoding, using [encoding_rs].
+//!
+//! Requires the `encoding_rs` feature, and Unix. On older systems and in
+//! environments configured for a legacy locale, such as `ja_JP.SJIS` or
+//! `zh_CN.GBK`, the bytes of the command line are not UTF-8, and decoding
+//! them as such would mangle every non-ASCII argument.
+//!
+//! ```
+//! let encoding = argv::encoding::locale_encoding().unwrap_or(encoding_rs::UTF_8);
+//! for arg in argv::encoding::decode(encoding) {
+//!     println!("{}", arg);
+//! }
+//! ```
+//!
+//! [encoding_rs]: https://docs.rs/encoding_rs
+
+use ::encoding_rs::Encoding;
+use std::borrow::Cow;
+use std::env;
+use std::os::unix::ffi::OsStrExt;
+
+/// Returns an iterator over command line arguments decoded from `encoding`.
+///
+/// Malformed sequences are replaced by U+FFFD REPLACEMENT CHARACTER. An
+/// argument is borrowed if its bytes are identical to its UTF-8 decoding,
+/// such as when it is ASCII.
+pub fn decode(encoding: &'static Encoding) -> Decode {
+    Decode {
+        inner: crate::iter(),
+        encoding,
+    }
+}
+
+/// Returns the encoding of the current locale, as selected by the `LC_ALL`,
+/// `LC_CTYPE` and `LANG` environment variables in that order of precedence.
+///
+/// The encoding is taken from the codeset part of the locale name, as in
+/// `ja_JP.SJIS` or `de_DE.ISO-8859-1@euro`. Returns None if the locale does
+/// not name a codeset, such as `C` or `POSIX`, or if encoding_rs does not
+/// recognize it.
+pub fn locale_encoding() -> Option<&'static Encoding> {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(env::var_os)
+        .find(|value| !value.is_empty())?;
+    let codeset = locale.as_bytes().splitn(2, |&b| b == b'.').nth(1)?;
+    let codeset = codeset.split(|&b| b == b'@').next()?;
+    Encoding::for_label(codeset)
+}
+
+/// Iterator over decoded command line arguments, returned by [`decode`].
+#[derive(Clone)]
+pub struct Decode {
+    inner: crate::Iter,
+    encoding: &'static Encoding,
+}
+
+impl Iterator for Decode {
+    type Item = Cow<'static, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arg = self.inner.next()?;
+        let (decoded, _had_errors) = self.encoding.decode_without_bom_handling(arg.as_bytes());
+        Some(decoded)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Decode {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
@@ -0,0 +1,110 @@
+// Support for argv::no_main!, which defines the C entry point of a #![no_main]
+// binary.
+
+use crate::once::OnceCell;
+use std::ffi::{CStr, OsStr};
+use std::fmt::Debug;
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+
+static ENTRY: OnceCell<&[&OsStr]> = OnceCell::new();
+
+/// Defines the C `main` function of a `#![no_main]` binary, which calls the
+/// given function with the command line arguments.
+///
+/// A binary that opts out of the standard library's startup code with
+/// `#![no_main]` receives its arguments only as `argc` and `argv` parameters
+/// of a C `main` function that it defines itself. This macro defines that
+/// function. The arguments are not copied: the function receives references
+/// to the strings the C runtime passed, and [`argv::iter()`][crate::iter]
+/// produces the same ones.
+///
+/// The function may return `()`, an `i32` exit code, or a `Result` whose
+/// error is printed with its `Debug` representation before exiting with
+/// code 1.
+///
+/// Available on Unix.
+///
+/// # Example
+///
+/// ```
+/// #![no_main]
+///
+/// use std::ffi::OsStr;
+///
+/// argv::no_main!(run);
+///
+/// fn run(args: &'static [&'static OsStr]) -> i32 {
+///     for arg in args {
+///         println!("{}", arg.to_string_lossy());
+///     }
+///     0
+/// }
+/// ```
+#[macro_export]
+macro_rules! no_main {
+    ($main:path) => {
+        #[no_mangle]
+        extern "C" fn main(
+            argc: $crate::__private::c_int,
+            argv: *const *const $crate::__private::c_char,
+        ) -> $crate::__private::c_int {
+            unsafe { $crate::__private::main(argc, argv, $main) }
+        }
+    };
+}
+
+#[doc(hidden)]
+pub unsafe fn main<F, R>(argc: c_int, argv: *const *const c_char, f: F) -> c_int
+where
+    F: FnOnce(&'static [&'static OsStr]) -> R,
+    R: Status,
+{
+    let args = ENTRY.get_or_init(|| {
+        let args: Vec<&'static OsStr> = if argc <= 0 || argv.is_null() {
+            Vec::new()
+        } else {
+            (0..argc as usize)
+                .map(|i| {
+                    let arg = unsafe { CStr::from_ptr(*argv.add(i)) };
+                    OsStr::from_bytes(arg.to_bytes())
+                })
+                .collect()
+        };
+        Box::leak(args.into_boxed_slice())
+    });
+    f(args).code()
+}
+
+pub(crate) fn current() -> Option<&'static [&'static OsStr]> {
+    ENTRY.get().copied()
+}
+
+#[doc(hidden)]
+pub trait Status {
+    fn code(self) -> c_int;
+}
+
+impl Status for () {
+    fn code(self) -> c_int {
+        0
+    }
+}
+
+impl Status for i32 {
+    fn code(self) -> c_int {
+        self as c_int
+    }
+}
+
+impl<E: Debug> Status for Result<(), E> {
+    fn code(self) -> c_int {
+        match self {
+            Ok(()) => 0,
+            Err(err) => {
+                eprintln!("Error: {:?}", err);
+                1
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use std::io;
+
+// Constructs an error carrying a message. In zero-alloc mode the message is
+// dropped, because attaching one requires a heap allocation.
+pub(crate) fn new(kind: io::ErrorKind, message: &'static str) -> io::Error {
+    if cfg!(feature = "zero-alloc") {
+        io::Error::from(kind)
+    } else {
+        io::Error::new(kind, message)
+    }
+}
+
+// Called before any operation that needs to allocate after the arguments have
+// been captured. Fails in zero-alloc mode.
+pub(crate) fn allocation() -> io::Result<()> {
+    if cfg!(feature = "zero-alloc") {
+        Err(io::Error::from(io::ErrorKind::Other))
+    } else {
+        Ok(())
+    }
+}
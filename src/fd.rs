@@ -0,0 +1,47 @@
+use std::io;
+use std::os::raw::{c_int, c_void};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+
+extern "C" {
+    fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+}
+
+/// Writes the command line arguments to a file descriptor, each followed by
+/// `terminator`, typically `b'\0'` or `b'\n'`.
+///
+/// This uses nothing but `write(2)`, making it safe to call from a signal
+/// handler, such as one for `SIGSEGV` that persists the invocation into a
+/// crash log. Arguments are obtained from [`raw_iter`][crate::raw_iter], with
+/// the caveats described there.
+///
+/// # Errors
+///
+/// Returns the error reported by `write(2)`, other than `EINTR` which is
+/// retried.
+pub fn write_to_fd(fd: RawFd, terminator: u8) -> io::Result<()> {
+    // Unguarded for the same reason as raw_iter().
+    for arg in crate::raw_iter() {
+        write_all(fd, arg.as_bytes())?;
+        write_all(fd, &[terminator])?;
+    }
+    Ok(())
+}
+
+fn write_all(fd: RawFd, mut bytes: &[u8]) -> io::Result<()> {
+    while !bytes.is_empty() {
+        let n = unsafe { write(fd, bytes.as_ptr().cast(), bytes.len()) };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if n == 0 {
+            return Err(io::Error::from(io::ErrorKind::WriteZero));
+        }
+        bytes = &bytes[n as usize..];
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::ffi::OsStr;
+
+/// Returns the index of the first command line argument equal to `needle`.
+///
+/// Index 0 is the program name, as in [`iter`][crate::iter].
+///
+/// # Example
+///
+/// ```
+/// if let Some(index) = argv::position_of("--") {
+///     let passthrough = argv::iter().skip(index + 1);
+///     # let _ = passthrough;
+/// }
+/// ```
+pub fn position_of<S: AsRef<OsStr>>(needle: S) -> Option<usize> {
+    let _guard = crate::zero_alloc::enter();
+    let needle = needle.as_ref();
+    crate::iter().position(|arg| arg == needle)
+}
+
+/// Returns the first command line argument for which `predicate` returns
+/// true.
+///
+/// # Example
+///
+/// ```
+/// let verbose = argv::find(|arg| arg == "-v" || arg == "--verbose").is_some();
+/// # let _ = verbose;
+/// ```
+pub fn find<P>(mut predicate: P) -> Option<&'static OsStr>
+where
+    P: FnMut(&OsStr) -> bool,
+{
+    let _guard = crate::zero_alloc::enter();
+    crate::iter().find(|arg| predicate(arg))
+}
+
+/// Returns the index of the first command line argument containing `needle`,
+/// and the byte offset at which it first occurs in that argument.
+///
+/// The search runs over the bytes of the arguments as they are, without
+/// converting them to strings. On Windows those are the [WTF-8] bytes
+/// produced by [`argv::bytes`][crate::bytes].
+///
+/// [WTF-8]: https://simonsapin.github.io/wtf-8/
+///
+/// # Example
+///
+/// ```
+/// let url_given = argv::find_containing(b"://").is_some();
+/// # let _ = url_given;
+/// ```
+pub fn find_containing(needle: &[u8]) -> Option<(usize, usize)> {
+    crate::bytes::iter()
+        .enumerate()
+        .find_map(|(index, arg)| Some((index, offset_of(&arg, needle)?)))
+}
+
+fn offset_of(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,53 @@
+use crate::Iter;
+use std::ffi::OsStr;
+
+/// Returns the first `N` command line arguments as an array, or None if there
+/// are fewer than `N`.
+///
+/// The array includes the program name as its first element, like
+/// [`iter`][crate::iter], and can be destructured directly. Any arguments
+/// after the first `N` are ignored; use [`split_first_n`] to also check for
+/// or process those.
+///
+/// # Example
+///
+/// ```no_run
+/// let [_, input, output] = match argv::first_n::<3>() {
+///     Some(args) => args,
+///     None => {
+///         eprintln!("usage: convert INPUT OUTPUT");
+///         std::process::exit(1);
+///     }
+/// };
+/// ```
+pub fn first_n<const N: usize>() -> Option<[&'static OsStr; N]> {
+    split_first_n().map(|(first, _rest)| first)
+}
+
+/// Returns the first `N` command line arguments as an array together with an
+/// iterator over the remaining ones, or None if there are fewer than `N`.
+///
+/// # Example
+///
+/// ```no_run
+/// // Expect exactly one argument after the program name.
+/// let path = match argv::split_first_n::<2>() {
+///     Some(([_, path], rest)) if rest.len() == 0 => path,
+///     _ => {
+///         eprintln!("usage: open PATH");
+///         std::process::exit(1);
+///     }
+/// };
+/// ```
+pub fn split_first_n<const N: usize>() -> Option<([&'static OsStr; N], Iter)> {
+    let _guard = crate::zero_alloc::enter();
+    let mut iter = crate::iter();
+    if iter.len() < N {
+        return None;
+    }
+    let mut first = [OsStr::new(""); N];
+    for slot in &mut first {
+        *slot = iter.next()?;
+    }
+    Some((first, iter))
+}
@@ -0,0 +1,92 @@
+//! Injecting candidate command lines from a fuzz target.
+//!
+//! Requires the `mock` feature. Under cargo-fuzz the real command line belongs
+//! to libFuzzer and holds its flags, so a fuzz target that exercises argument
+//! parsing installs each candidate before running the code under test:
+//!
+//! ```
+//! # use std::ffi::OsStr;
+//! # fn parse_cli() {}
+//! fn fuzz_target(data: &[u8]) {
+//!     let input = String::from_utf8_lossy(data);
+//!     let args: Vec<&OsStr> = input.split('\0').map(OsStr::new).collect();
+//!     unsafe { argv::fuzz::install(&args) };
+//!     parse_cli();
+//! }
+//! # fuzz_target(b"prog\0--flag");
+//! ```
+//!
+//! Unlike [`argv::mock`][crate::mock], installing arguments here does not leak
+//! memory. The storage is reused from one call to the next, so after the
+//! first few inputs an install does not allocate either.
+
+use std::cell::UnsafeCell;
+use std::ffi::{OsStr, OsString};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+struct Storage(UnsafeCell<Inner>);
+
+struct Inner {
+    strings: Vec<OsString>,
+    args: Vec<&'static OsStr>,
+}
+
+// Only install() mutates the storage, and its safety contract forbids it from
+// running concurrently with argv::iter(), the only reader, or with another
+// install().
+unsafe impl Sync for Storage {}
+
+static STORAGE: Storage = Storage(UnsafeCell::new(Inner {
+    strings: Vec::new(),
+    args: Vec::new(),
+}));
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Makes `argv::iter()` produce `args` until the next call to `install` or
+/// [`uninstall`].
+///
+/// Overrides installed through [`argv::mock`][crate::mock] take precedence.
+///
+/// # Safety
+///
+/// Arguments obtained from `argv::iter()` before this call must not be used
+/// after it, because their memory is reused. This function must not be called
+/// while another thread might be calling `argv::iter()`. Fuzz targets satisfy
+/// both requirements as long as they drop everything they derived from the
+/// previous input.
+pub unsafe fn install(args: &[&OsStr]) {
+    INSTALLED.store(false, Ordering::Release);
+    let storage = unsafe { &mut *STORAGE.0.get() };
+    storage.args.clear();
+    if storage.strings.len() < args.len() {
+        storage.strings.resize_with(args.len(), OsString::new);
+    }
+    for (string, arg) in storage.strings.iter_mut().zip(args) {
+        string.clear();
+        string.push(arg);
+    }
+    for string in &storage.strings[..args.len()] {
+        // The heap buffer of each string stays put until the next install.
+        storage
+            .args
+            .push(unsafe { &*(string.as_os_str() as *const OsStr) });
+    }
+    INSTALLED.store(true, Ordering::Release);
+}
+
+/// Returns `argv::iter()` to the real command line.
+///
+/// # Safety
+///
+/// Same requirements as [`install`].
+pub unsafe fn uninstall() {
+    INSTALLED.store(false, Ordering::Release);
+}
+
+pub(crate) fn current() -> Option<&'static [&'static OsStr]> {
+    if INSTALLED.load(Ordering::Acquire) {
+        Some(unsafe { &(*STORAGE.0.get()).args })
+    } else {
+        None
+    }
+}
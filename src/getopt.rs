@@ -0,0 +1,196 @@
+//! Running C option parsers such as `getopt(3)` over the command line.
+//!
+//! `getopt` and `getopt_long` take `char *argv[]` and may reorder it: the GNU
+//! implementation moves operands after the options, and reports through the
+//! global `optind` where the operands begin. [`Argv`] holds a mutable copy of
+//! the command line for them to work on, and translates positions in the
+//! possibly reordered copy back to indices into
+//! [`argv::iter()`][crate::iter].
+//!
+//! ```
+//! use argv::getopt::Argv;
+//! use std::os::raw::{c_char, c_int};
+//!
+//! extern "C" {
+//!     fn getopt(argc: c_int, argv: *const *mut c_char, optstring: *const c_char) -> c_int;
+//! }
+//!
+//! let mut argv = Argv::new();
+//! argv.run(|argc, ptr| {
+//!     while unsafe { getopt(argc, ptr, b"vo:\0".as_ptr().cast()) } != -1 {
+//!         // handle the option
+//!     }
+//! });
+//! for index in argv.operands() {
+//!     let operand = argv::iter().nth(index).unwrap();
+//!     # let _ = operand;
+//! }
+//! ```
+
+use std::ffi::OsStr;
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+extern "C" {
+    static mut optind: c_int;
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+))]
+extern "C" {
+    static mut optreset: c_int;
+}
+
+// Serializes use of getopt's global state by Argv::run.
+static LOCK: AtomicBool = AtomicBool::new(false);
+
+/// Mutable, NULL-terminated copy of the command line in the form taken by
+/// `getopt` and `getopt_long`.
+pub struct Argv {
+    // NUL-terminated copies of the arguments, which ptrs point into.
+    strings: Vec<Box<[u8]>>,
+    ptrs: Vec<*mut c_char>,
+    optind: usize,
+}
+
+impl Argv {
+    /// Copies the arguments produced by [`argv::iter()`][crate::iter].
+    pub fn new() -> Self {
+        let mut strings: Vec<Box<[u8]>> = crate::iter()
+            .map(|arg| {
+                let mut bytes = Vec::with_capacity(arg.len() + 1);
+                bytes.extend_from_slice(arg.as_bytes());
+                bytes.push(0);
+                bytes.into_boxed_slice()
+            })
+            .collect();
+        let mut ptrs: Vec<*mut c_char> = strings
+            .iter_mut()
+            .map(|string| string.as_mut_ptr().cast())
+            .collect();
+        ptrs.push(ptr::null_mut());
+        Argv {
+            strings,
+            ptrs,
+            optind: 1,
+        }
+    }
+
+    /// The number of arguments, to pass as `argc`.
+    pub fn argc(&self) -> c_int {
+        self.strings.len() as c_int
+    }
+
+    /// The pointer to pass as `argv`.
+    pub fn as_mut_ptr(&mut self) -> *mut *mut c_char {
+        self.ptrs.as_mut_ptr()
+    }
+
+    /// Calls `f` with `argc` and `argv`, after resetting the state of
+    /// `getopt` so that it starts parsing from the beginning, and records the
+    /// value of `optind` that `f` leaves behind.
+    ///
+    /// Calls to `run` from different threads take turns, since `getopt`
+    /// keeps its state in globals.
+    pub fn run<R>(&mut self, f: impl FnOnce(c_int, *mut *mut c_char) -> R) -> R {
+        while LOCK
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            thread::yield_now();
+        }
+        let _unlock = Unlock;
+
+        unsafe { reset() };
+        let argc = self.argc();
+        let result = f(argc, self.as_mut_ptr());
+        let end = unsafe { *ptr::addr_of!(optind) };
+        self.optind = (end.max(1) as usize).min(self.strings.len());
+        result
+    }
+
+    /// The index into `argv::iter()` of the argument that is at `position`
+    /// in the array now, after any reordering.
+    pub fn original_index(&self, position: usize) -> Option<usize> {
+        let ptr = *self.ptrs[..self.strings.len()].get(position)?;
+        self.strings
+            .iter()
+            .position(|string| string.as_ptr() == ptr as *const u8)
+    }
+
+    /// Indices into `argv::iter()` of the program name and of the options and
+    /// option values consumed by the last [`run`][Argv::run], in the order
+    /// they are now in the array.
+    pub fn consumed(&self) -> Vec<usize> {
+        (0..self.optind)
+            .filter_map(|position| self.original_index(position))
+            .collect()
+    }
+
+    /// Indices into `argv::iter()` of the operands left after the options by
+    /// the last [`run`][Argv::run], in the order they are now in the array.
+    pub fn operands(&self) -> Vec<usize> {
+        (self.optind..self.strings.len())
+            .filter_map(|position| self.original_index(position))
+            .collect()
+    }
+
+    /// The argument at `position` in the array now, after any reordering.
+    pub fn get(&self, position: usize) -> Option<&OsStr> {
+        let index = self.original_index(position)?;
+        let string = &self.strings[index];
+        Some(OsStr::from_bytes(&string[..string.len() - 1]))
+    }
+}
+
+impl Default for Argv {
+    fn default() -> Self {
+        Argv::new()
+    }
+}
+
+struct Unlock;
+
+impl Drop for Unlock {
+    fn drop(&mut self) {
+        LOCK.store(false, Ordering::Release);
+    }
+}
+
+// glibc and musl fully reinitialize when optind is set to 0. The BSDs use a
+// separate optreset flag instead.
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+)))]
+unsafe fn reset() {
+    unsafe { *ptr::addr_of_mut!(optind) = 0 };
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+))]
+unsafe fn reset() {
+    unsafe {
+        *ptr::addr_of_mut!(optreset) = 1;
+        *ptr::addr_of_mut!(optind) = 1;
+    }
+}
@@ -0,0 +1,69 @@
+use std::ffi::{CStr, OsStr};
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+
+mod ffi {
+    use std::os::raw::c_char;
+
+    extern "C" {
+        pub(super) static mut program_invocation_name: *mut c_char;
+        pub(super) static mut program_invocation_short_name: *mut c_char;
+    }
+}
+
+/// Returns glibc's `program_invocation_name`, the value of `argv[0]` as seen
+/// by the C library.
+///
+/// glibc initializes this before any static constructor runs, so it is
+/// available even in situations where this crate's own capture of the command
+/// line did not happen.
+///
+/// Only available on Linux with glibc. Returns None under Miri, which does
+/// not model glibc's globals.
+pub fn program_invocation_name() -> Option<&'static OsStr> {
+    if cfg!(miri) {
+        return None;
+    }
+    unsafe { from_ptr(ffi::program_invocation_name) }
+}
+
+/// Returns glibc's `program_invocation_short_name`, the part of
+/// [`program_invocation_name`] following the last `/`.
+///
+/// Only available on Linux with glibc. Returns None under Miri.
+pub fn program_invocation_short_name() -> Option<&'static OsStr> {
+    if cfg!(miri) {
+        return None;
+    }
+    unsafe { from_ptr(ffi::program_invocation_short_name) }
+}
+
+unsafe fn from_ptr(ptr: *const c_char) -> Option<&'static OsStr> {
+    if ptr.is_null() {
+        None
+    } else {
+        let c_str = unsafe { CStr::from_ptr(ptr) };
+        Some(OsStr::from_bytes(c_str.to_bytes()))
+    }
+}
+
+// Called when the crate moves the arguments out of the memory the kernel
+// placed them in, so that glibc keeps reporting the original argv[0] rather
+// than whatever gets written over it.
+#[cfg_attr(any(miri, feature = "force-fallback"), allow(dead_code))]
+pub(crate) unsafe fn relocate(old_argv0: *const c_char, new_argv0: *const c_char) {
+    unsafe {
+        let name = ffi::program_invocation_name as *const c_char;
+        let short_name = ffi::program_invocation_short_name as *const c_char;
+        if name != old_argv0 {
+            return;
+        }
+        let len = CStr::from_ptr(old_argv0).to_bytes().len();
+        let old_end = old_argv0.add(len);
+        ffi::program_invocation_name = new_argv0 as *mut c_char;
+        if short_name >= old_argv0 && short_name <= old_end {
+            let offset = short_name as usize - old_argv0 as usize;
+            ffi::program_invocation_short_name = new_argv0.add(offset) as *mut c_char;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::source::Source;
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::vec;
+
+/// Returns an iterator over command line arguments obtained from a specific
+/// source, read anew on every call.
+///
+/// Unlike [`iter`][crate::iter], this ignores [`source::order`] and whatever
+/// source the arguments were first obtained from. It is for inspecting the
+/// sources themselves, for example reading `/proc/self/cmdline` to observe
+/// what a process listing shows after the arguments were modified at
+/// runtime, even though the constructor captured them successfully.
+///
+/// Arguments that remain valid for the rest of the program, such as those
+/// captured by the constructor, are borrowed. Sources that must be read into
+/// memory, such as [`Source::Procfs`] and [`Source::Std`], produce owned
+/// arguments that are freed when dropped. A source that is unavailable
+/// produces nothing.
+///
+/// [`source::order`]: crate::source::order
+///
+/// # Example
+///
+/// ```
+/// use argv::source::Source;
+///
+/// for arg in argv::iter_with(Source::Procfs) {
+///     println!("{}", arg.to_string_lossy());
+/// }
+/// ```
+pub fn iter_with(source: Source) -> IterWith {
+    IterWith {
+        inner: crate::source::read_fresh(source).into_iter(),
+    }
+}
+
+/// Iterator over command line arguments from a specific source, returned by
+/// [`iter_with`].
+pub struct IterWith {
+    inner: vec::IntoIter<Cow<'static, OsStr>>,
+}
+
+impl Iterator for IterWith {
+    type Item = Cow<'static, OsStr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for IterWith {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl DoubleEndedIterator for IterWith {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
@@ -0,0 +1,31 @@
+/// Returns a [`lexopt::Parser`] over the command line.
+///
+/// Requires the `lexopt` feature. Equivalent to `lexopt::Parser::from_env()`,
+/// except that the arguments come from [`argv::iter()`][crate::iter], so an
+/// override installed through [`argv::mock`][crate::mock] is seen by the
+/// parser too. The parser pulls arguments from the iterator as it goes rather
+/// than collecting them into a `Vec<OsString>` up front.
+///
+/// [`lexopt::Parser`]: https://docs.rs/lexopt/0.3/lexopt/struct.Parser.html
+///
+/// # Example
+///
+/// ```
+/// use lexopt::prelude::*;
+///
+/// fn main() -> Result<(), lexopt::Error> {
+///     let mut verbose = false;
+///     let mut parser = argv::lexopt_parser();
+///     while let Some(arg) = parser.next()? {
+///         match arg {
+///             Short('v') | Long("verbose") => verbose = true,
+///             _ => {}
+///         }
+///     }
+///     # let _ = verbose;
+///     Ok(())
+/// }
+/// ```
+pub fn lexopt_parser() -> ::lexopt::Parser {
+    ::lexopt::Parser::from_iter(crate::iter())
+}
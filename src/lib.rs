@@ -32,10 +32,72 @@
 //! arguments naturally live for the duration of the program. This crate
 //! implements the same API on other platforms as well, such as Windows, but
 //! leaks memory on platforms other than Linux and macOS.
+//!
+//! The `force-fallback` feature selects the portable implementation, based on
+//! `std::env::args_os`, on every platform. This is useful for comparing the
+//! two implementations and with linkers that discard `.init_array`.
+//!
+//! The `no-ctor` feature removes every static constructor from this crate, for
+//! environments that forbid code running before main. It implies
+//! `force-fallback`, so the arguments are collected lazily on first use and
+//! leaked. On Linux with glibc they are instead located on first use in the
+//! memory the kernel placed them in, through `__libc_stack_end`, so that the
+//! strings are still not copied; see [`Source::Libc`][source::Source::Libc].
+//! In addition, the functions that operate on the memory the kernel placed the
+//! arguments in, such as [`title::set`], [`scrub`] and [`take_secret`], return
+//! an error, [`stack_region`] returns None, and [`raw_iter`] produces nothing
+//! until [`iter`] has been called once.
+//!
+//! The `preinit` feature additionally captures the arguments from
+//! `.preinit_array` on Linux, which runs before any `.init_array` constructor
+//! in the process. This makes [`iter`] reliable inside other crates'
+//! constructors, such as those of logging frameworks or `ctor`-based
+//! registries, whose order relative to this crate's constructor is otherwise
+//! unspecified. Only executables may have a `.preinit_array`, so this feature
+//! must not be enabled in a crate that is built as a shared library.
+//!
+//! The `ctor-priority-101`, `ctor-priority-1000` and `ctor-priority-10000`
+//! features give the constructor that captures the arguments on Linux the
+//! corresponding priority, the same as `__attribute__((constructor(N)))` in C.
+//! Constructors run in increasing order of priority, and before all
+//! constructors without one, so a project whose own early constructors use
+//! [`iter`] can enable a priority lower than theirs. If several of these
+//! features are enabled, the lowest priority applies. Unlike `preinit`, these
+//! work in shared libraries, but only order constructors within the same
+//! executable or library.
+//!
+//! The `snapshot` feature makes the constructor copy the arguments into memory
+//! owned by this crate as soon as it captures them, the same way [`title::set`]
+//! does before its first write, instead of borrowing the memory the kernel
+//! placed them in. C libraries that rewrite `argv` to change the process
+//! title, and this crate's own [`title::set`], then cannot change what
+//! [`iter`] produces, which matters for programs that set the title and parse
+//! the arguments again afterward. Only [`take_secret`] still removes an
+//! argument from the copy, as requested. With `no-ctor` the copy is taken on
+//! first use instead.
+//!
+//! The `nightly` feature implements the unstable `TrustedLen` trait for
+//! [`Iter`], which lets `collect` and `zip` rely on its length. It requires a
+//! nightly compiler.
+//!
+//! The `debug-check` feature guards against bugs in the capture before main.
+//! In builds with debug assertions, the first use of the captured arguments
+//! compares them against `std::env::args_os` and prints any mismatch to
+//! stderr, or panics if the `ARGV_DEBUG_CHECK` environment variable is set to
+//! `panic`. Setting it to `off` skips the check. Only the Linux implementation
+//! is checked, since the portable one is itself based on `std::env::args_os`.
+//!
+//! The crate also works when compiled into a shared library that is injected
+//! into another program with `LD_PRELOAD`, as profilers and fault injectors
+//! are. glibc passes the host program's arguments to the library's static
+//! constructor as usual. If a loader passes none, the arguments are taken
+//! from the next source in [`source::order`] instead.
 
 #![doc(html_root_url = "https://docs.rs/argv/0.1.11")]
+#![cfg_attr(feature = "nightly", feature(trusted_len))]
 #![deny(unsafe_op_in_unsafe_fn)]
 #![allow(
+    clippy::cast_possible_truncation,
     clippy::cast_sign_loss,
     clippy::extra_unused_type_parameters,
     clippy::let_underscore_untyped,
@@ -44,69 +106,884 @@
     clippy::similar_names
 )]
 
+pub mod annotation;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub mod apple;
+mod arg_max;
+#[cfg(feature = "argh")]
+pub mod argh;
+mod args;
+pub mod audit;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod auxv;
+#[cfg(feature = "bstr")]
+pub mod bstr;
+pub mod bytes;
+#[cfg(feature = "c-abi")]
+mod c_abi;
+mod c_str;
+#[cfg(feature = "camino")]
+pub mod camino;
+#[cfg(feature = "clap")]
+pub mod clap;
+mod command;
+#[cfg(all(
+    feature = "debug-check",
+    target_os = "linux",
+    not(target_env = "musl"),
+    not(miri),
+    not(feature = "force-fallback"),
+))]
+mod debug_check;
+mod display;
+mod dump;
+pub mod embed;
+#[cfg(all(feature = "encoding_rs", unix))]
+pub mod encoding;
+#[cfg(unix)]
+mod entry;
+mod error;
+#[cfg(unix)]
+mod fd;
+mod find;
+mod first_n;
+#[cfg(feature = "mock")]
+pub mod fuzz;
+#[cfg(unix)]
+pub mod getopt;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+mod invocation;
+mod iter_with;
+#[cfg(feature = "lexopt")]
+mod lexopt;
+pub mod libtest;
+mod lock;
+#[cfg(feature = "log")]
+pub mod log;
+mod lossy;
+mod main_args;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(unix)]
+pub mod mpi;
+#[cfg(feature = "unicode-normalization")]
+pub mod nfc;
+mod once;
+#[cfg(feature = "opentelemetry")]
+pub mod opentelemetry;
+mod panic_hook;
+#[cfg(feature = "pico-args")]
+mod pico_args;
+mod process;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod procfs;
+mod program;
+mod record;
+pub mod redact;
+#[cfg(unix)]
+mod reexec;
+mod report;
+mod secret;
+mod secure;
+#[cfg(feature = "serde")]
+mod serde;
+pub mod source;
+mod spans;
+mod stats;
+pub mod test;
+pub mod title;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+mod underline;
+mod utf8;
+mod vars;
+pub mod worker;
+#[cfg(feature = "zero-alloc")]
+pub mod zero_alloc;
+#[cfg(not(feature = "zero-alloc"))]
+mod zero_alloc {
+    pub(crate) struct Guard;
+
+    pub(crate) fn enter() -> Guard {
+        Guard
+    }
+
+    #[allow(dead_code)] // only the fallback implementation allocates lazily
+    pub(crate) fn exempt<T>(f: impl FnOnce() -> T) -> T {
+        f()
+    }
+}
+
 use std::ffi::OsStr;
+use std::io;
+use std::ops::Range;
+
+/// Passes the command line arguments to `main` as a parameter.
+///
+/// The parameter may be of type [`Args`], producing each argument as
+/// `&'static str`, or [`Iter`], producing `&'static OsStr`. Either way the
+/// arguments are produced by reference, without copying. The function may
+/// declare a return type, such as `Result<(), E>`, as with an ordinary
+/// `main`.
+///
+/// ```
+/// #[argv::main]
+/// fn main(args: argv::Args) {
+///     for arg in args.skip(1) {
+///         println!("{}", arg);
+///     }
+/// }
+/// ```
+pub use argv_impl::main;
+
+pub use crate::arg_max::{arg_budget, arg_cost, arg_max};
+pub use crate::args::{args, Args};
+pub use crate::bytes::{encoded_bytes, EncodedBytes};
+#[cfg(feature = "c-abi")]
+pub use crate::c_abi::{rust_argv_argc, rust_argv_argv};
+pub use crate::c_str::{as_exec_array, c_strs, leak_args, CStrs};
+#[cfg(unix)]
+pub use crate::c_str::{iter_c_ptrs, CPtrs};
+pub use crate::command::command;
+pub use crate::display::{display, ArgDisplay};
+pub use crate::dump::debug_dump;
+#[cfg(unix)]
+pub use crate::fd::write_to_fd;
+pub use crate::find::{find, find_containing, position_of};
+pub use crate::first_n::{first_n, split_first_n};
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub use crate::invocation::{program_invocation_name, program_invocation_short_name};
+pub use crate::iter_with::{iter_with, IterWith};
+#[cfg(feature = "lexopt")]
+pub use crate::lexopt::lexopt_parser;
+pub use crate::lossy::{lossy, Lossy, Replacement};
+pub use crate::panic_hook::install_panic_hook;
+#[cfg(feature = "pico-args")]
+pub use crate::pico_args::pico_args_arguments;
+pub use crate::process::{of_pid, parent, processes, Processes};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use crate::procfs::{verify_against_procfs, Divergence};
+pub use crate::program::{current_exe_static, program, resolve_program_path};
+pub use crate::record::record_to;
+#[cfg(feature = "mock")]
+pub use crate::record::replay_from;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use crate::reexec::reexec_proc_self_exe;
+#[cfg(unix)]
+pub use crate::reexec::{reexec, reexec_with, Reexec};
+pub use crate::report::{report, Report};
+pub use crate::secret::{scrub, take_secret, SecretArg};
+pub use crate::secure::is_secure_exec;
+pub use crate::spans::{spans, Span};
+pub use crate::stats::{stats, total_bytes, Stats};
+pub use crate::underline::{underline, underline_range, Underline};
+pub use crate::vars::{vars, Vars};
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(unix)]
+    pub use crate::entry::{main, Status};
+    pub use crate::main_args::MainArgs;
+    #[cfg(unix)]
+    pub use std::os::raw::{c_char, c_int};
+}
 
 /// Returns an iterator over command line arguments.
+///
+/// The first item is ordinarily the program name, but a program can be
+/// executed with no arguments at all, not even `argv[0]`, in which case this
+/// iterator is empty. Use [`program`] for a name that is always available.
 pub fn iter() -> Iter {
+    let _guard = crate::zero_alloc::enter();
+    #[cfg(feature = "mock")]
+    {
+        if let Some(args) = crate::mock::current() {
+            return Iter {
+                inner: Inner::Slice(args.iter()),
+            };
+        }
+    }
+    if let Some(args) = crate::embed::current() {
+        return Iter {
+            inner: Inner::Slice(args.iter()),
+        };
+    }
+    #[cfg(unix)]
+    {
+        if let Some(args) = crate::entry::current() {
+            return Iter {
+                inner: Inner::Slice(args.iter()),
+            };
+        }
+    }
+    if let Some(args) = crate::source::current() {
+        return Iter {
+            inner: Inner::Slice(args.iter()),
+        };
+    }
     Iter {
-        platform_specific: crate::r#impl::iter(),
+        inner: Inner::native(crate::r#impl::iter()),
     }
 }
 
+/// Returns an iterator over command line arguments that is safe to use inside
+/// a signal handler.
+///
+/// Neither this function nor the returned iterator allocates, takes a lock, or
+/// makes a system call, so it can be called from crash handlers to include the
+/// command line in a report.
+///
+/// On Linux with glibc this produces the same arguments as [`iter`]. On other
+/// platforms, where the arguments are collected into memory owned by this
+/// crate the first time `iter` is called, it produces nothing if `iter` has
+/// not been called before. Call `iter` once at startup if the arguments need
+/// to be available in a signal handler later.
+pub fn raw_iter() -> Iter {
+    // No zero_alloc guard, because its thread local may allocate on first
+    // access in a shared library.
+    Iter {
+        inner: Inner::native(crate::r#impl::raw_iter()),
+    }
+}
+
+/// Checks whether the memory that the strings produced by [`iter`] point into
+/// is still unmodified.
+///
+/// A checksum of the arguments is computed when they are captured. Some other
+/// component of the process, such as a C library implementing its own
+/// `setproctitle`, may later overwrite the memory the kernel placed the
+/// arguments in, which silently changes the contents of the `&'static OsStr`
+/// previously handed out by this crate. This function recomputes the checksum
+/// and returns false if that has happened.
+///
+/// Modifications made through this crate, such as [`title::set`], do not
+/// count, because they are preceded by moving the arguments into memory
+/// private to this crate.
+pub fn verify() -> bool {
+    let _guard = crate::zero_alloc::enter();
+    crate::r#impl::verify()
+}
+
+/// Updates what [`iter`] produces after C code removed arguments from the
+/// argument array.
+///
+/// Some C libraries take `argc` and `argv` by pointer and remove the
+/// arguments they recognize, such as GTK in `gtk_init(&argc, &argv)`. They
+/// do so by moving the pointers to the remaining arguments down and ending
+/// the array with a null pointer sooner. On Linux with glibc, the array
+/// returned by [`as_exec_array`] is the one this crate reads the arguments
+/// from, so after passing it to such a library, call this function to make
+/// `iter` produce only the arguments that are left. The new arguments are
+/// the ones up to the first null pointer, and [`verify`] is updated to
+/// consider them unmodified.
+///
+/// The C code may only move the pointers the array already holds and replace
+/// them with null pointers, and nothing may iterate over the arguments
+/// between its modification and this call. The strings themselves are not
+/// moved or freed, so references previously produced by `iter` remain valid.
+/// Iterators created before this call, including those of [`args`] and
+/// [`c_strs`], stay bounded by the old number of arguments: they read the
+/// array as it is now and produce an empty string for each position past its
+/// new end. [`spans`] returns None once the number of arguments has changed.
+///
+/// # Errors
+///
+/// Fails on platforms other than Linux with glibc, where the arguments are
+/// collected into memory owned by this crate and the array given to C is a
+/// separate copy.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::os::raw::{c_char, c_int};
+///
+/// # unsafe fn gtk_init(_argc: *mut c_int, _argv: *mut *mut *mut c_char) {}
+/// # /*
+/// extern "C" {
+///     fn gtk_init(argc: *mut c_int, argv: *mut *mut *mut c_char);
+/// }
+/// # */
+///
+/// let mut argc = argv::iter().len() as c_int;
+/// let mut array = argv::as_exec_array() as *mut *mut c_char;
+/// unsafe { gtk_init(&mut argc, &mut array) };
+/// argv::refresh().unwrap();
+/// ```
+pub fn refresh() -> io::Result<()> {
+    let _guard = crate::zero_alloc::enter();
+    crate::r#impl::refresh()
+}
+
+/// Returns the bounds of the contiguous memory region in which the kernel
+/// placed the command line argument and environment strings at startup.
+///
+/// On Linux the argument strings, each followed by a NUL terminator, are laid
+/// out back to back in the initial process stack, immediately followed by the
+/// environment strings. The returned range starts at the first byte of
+/// `argv[0]` and ends one past the NUL terminator of the last string that is
+/// still contiguous with the ones before it.
+///
+/// The pointers are raw and no guarantee is made about what other code in the
+/// process may be doing with this memory. Dereferencing them is unsafe; in
+/// particular, writing into the region invalidates any reference previously
+/// returned by [`iter`].
+///
+/// Returns None on platforms other than Linux, or if the arguments were not
+/// captured.
+pub fn stack_region() -> Option<Range<*mut u8>> {
+    let _guard = crate::zero_alloc::enter();
+    crate::r#impl::stack_region()
+}
+
+/// Returns the start and length of the contiguous memory block in which the
+/// kernel placed the command line argument strings at startup.
+///
+/// This is the part of [`stack_region`] holding the arguments, without the
+/// environment that follows them: from the first byte of `argv[0]` to one
+/// past the NUL terminator of the last argument that is contiguous with the
+/// ones before it. [`title::set`] and [`scrub`] are built on writing into
+/// this block, after first moving the arguments out of it.
+///
+/// Writing through the pointer is unsafe and only sound once nothing refers
+/// into the block any longer. References returned by [`iter`] before the
+/// arguments were relocated do point into it, and other code in the process,
+/// such as a C library's own `setproctitle`, may be using it too.
+///
+/// Returns None on platforms other than Linux, or if the arguments were not
+/// captured.
+pub fn raw_region() -> Option<(*mut u8, usize)> {
+    let _guard = crate::zero_alloc::enter();
+    let region = crate::r#impl::args_region()?;
+    Some((region.start, region.end as usize - region.start as usize))
+}
+
 /// Iterator over command line arguments.
+#[derive(Clone)]
 pub struct Iter {
-    platform_specific: crate::r#impl::Iter,
+    inner: Inner,
+}
+
+#[derive(Clone)]
+enum Inner {
+    // Along with the number of arguments when the iterator was created, from
+    // which the position of each argument follows. The global count may have
+    // been reduced since by argv::refresh(), but the iterator's is not.
+    Native(crate::r#impl::Iter, usize),
+    Slice(std::slice::Iter<'static, &'static OsStr>),
+}
+
+impl Inner {
+    fn native(platform_specific: crate::r#impl::Iter) -> Self {
+        let argc = platform_specific.len();
+        Inner::Native(platform_specific, argc)
+    }
 }
 
 impl Iterator for Iter {
     type Item = &'static OsStr;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.platform_specific.next()
+        match &mut self.inner {
+            Inner::Native(platform_specific, _argc) => platform_specific.next(),
+            Inner::Slice(slice) => slice.next().copied(),
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.platform_specific.size_hint()
+        match &self.inner {
+            Inner::Native(platform_specific, _argc) => platform_specific.size_hint(),
+            Inner::Slice(slice) => slice.size_hint(),
+        }
     }
 }
 
 impl ExactSizeIterator for Iter {
     fn len(&self) -> usize {
-        self.platform_specific.len()
+        match &self.inner {
+            Inner::Native(platform_specific, _argc) => platform_specific.len(),
+            Inner::Slice(slice) => slice.len(),
+        }
     }
 }
 
-#[cfg(all(target_os = "linux", not(target_env = "musl")))]
+// Every implementation reports its exact length in size_hint.
+#[cfg(feature = "nightly")]
+unsafe impl std::iter::TrustedLen for Iter {}
+
+impl Iter {
+    // Whether this produces the process's own arguments, as opposed to ones
+    // installed by argv::mock or argv::embed.
+    pub(crate) fn is_native(&self) -> bool {
+        match self.inner {
+            Inner::Native(..) => true,
+            Inner::Slice(_) => false,
+        }
+    }
+
+    // Like next() followed by OsStr::to_str, but the process's own arguments
+    // are each validated as UTF-8 only the first time.
+    pub(crate) fn next_str(&mut self) -> Option<Result<&'static str, &'static OsStr>> {
+        let (arg, string) = match &mut self.inner {
+            Inner::Native(platform_specific, argc) => {
+                let index = *argc - platform_specific.len();
+                let arg = platform_specific.next()?;
+                (arg, crate::utf8::native_to_str(index, arg))
+            }
+            Inner::Slice(slice) => {
+                let arg = *slice.next()?;
+                (arg, arg.to_str())
+            }
+        };
+        Some(string.ok_or(arg))
+    }
+}
+
+// Miri runs .init_array constructors without arguments, and has no way to
+// model the kernel's argument memory, so it uses the portable implementation.
+#[cfg(all(
+    target_os = "linux",
+    not(target_env = "musl"),
+    not(miri),
+    not(feature = "force-fallback"),
+))]
 mod r#impl {
     use std::ffi::{CStr, OsStr};
+    use std::io;
     use std::mem;
+    use std::ops::Range;
     use std::os::raw::{c_char, c_int};
     use std::os::unix::ffi::OsStrExt;
-    use std::ptr;
+    use std::sync::atomic::{AtomicI32, AtomicPtr, AtomicUsize, Ordering};
+    use std::sync::Once;
+    use std::{ptr, slice, thread};
 
-    static mut ARGC: c_int = 0;
-    static mut ARGV: *const *const c_char = ptr::null();
+    use crate::source::Source;
 
+    pub(crate) const SOURCE: Source = Source::Constructor;
+
+    // Written by the constructor before main, except that ARGV is repointed by
+    // relocate() and ARGC is reduced by refresh() while STATE is RELOCATING.
+    // ORIGINAL_ARGV is stored last, so that observing it non-null makes the
+    // others visible.
+    static ARGC: AtomicI32 = AtomicI32::new(0);
+    static ARGV: AtomicPtr<*const c_char> = AtomicPtr::new(ptr::null_mut());
+    static ENVP: AtomicPtr<*const c_char> = AtomicPtr::new(ptr::null_mut());
+    // The argc and argv passed by the kernel, even after ARGV has been
+    // relocated or ARGC refreshed.
+    static ORIGINAL_ARGC: AtomicI32 = AtomicI32::new(0);
+    static ORIGINAL_ARGV: AtomicPtr<*const c_char> = AtomicPtr::new(ptr::null_mut());
+
+    // Sections named .init_array.NNNNN run in increasing order of NNNNN,
+    // before the unnumbered .init_array.
     #[cfg(target_os = "linux")]
-    #[link_section = ".init_array"]
+    #[cfg_attr(feature = "ctor-priority-101", link_section = ".init_array.00101")]
+    #[cfg_attr(
+        all(feature = "ctor-priority-1000", not(feature = "ctor-priority-101")),
+        link_section = ".init_array.01000"
+    )]
+    #[cfg_attr(
+        all(
+            feature = "ctor-priority-10000",
+            not(any(feature = "ctor-priority-101", feature = "ctor-priority-1000")),
+        ),
+        link_section = ".init_array.10000"
+    )]
+    #[cfg_attr(
+        not(any(
+            feature = "ctor-priority-101",
+            feature = "ctor-priority-1000",
+            feature = "ctor-priority-10000",
+        )),
+        link_section = ".init_array"
+    )]
+    #[used]
+    static CAPTURE: unsafe extern "C" fn(c_int, *const *const c_char, *const *const c_char) =
+        capture;
+
+    // Runs before every .init_array constructor in the process, including
+    // those of shared libraries, so that their constructors can use the
+    // arguments too. Linkers reject .preinit_array in shared objects, hence
+    // opt-in.
+    #[cfg(all(target_os = "linux", feature = "preinit"))]
+    #[link_section = ".preinit_array"]
     #[used]
-    static CAPTURE: unsafe extern "C" fn(c_int, *const *const c_char) = capture;
+    static PREINIT_CAPTURE: unsafe extern "C" fn(
+        c_int,
+        *const *const c_char,
+        *const *const c_char,
+    ) = capture;
 
     // Disabled for now until we investigate https://github.com/dtolnay/argv/issues/1
     #[cfg_attr(target_os = "macos", link_section = "__DATA,__mod_init_func")]
     #[allow(dead_code)]
-    unsafe extern "C" fn capture(argc: c_int, argv: *const *const c_char) {
-        unsafe {
-            ARGC = argc;
-            ARGV = argv;
+    unsafe extern "C" fn capture(
+        argc: c_int,
+        argv: *const *const c_char,
+        envp: *const *const c_char,
+    ) {
+        // Not every loader passes arguments to the constructors of shared
+        // objects, such as one injected through LD_PRELOAD. Leave nothing
+        // captured in that case, so that the next source in
+        // argv::source::order() is used instead.
+        if argc < 0 || argv.is_null() {
+            return;
+        }
+        // Already captured from .preinit_array, after which the arguments may
+        // have been borrowed or relocated by another constructor.
+        if captured() {
+            return;
+        }
+        ARGC.store(argc, Ordering::Relaxed);
+        ORIGINAL_ARGC.store(argc, Ordering::Relaxed);
+        ARGV.store(argv as *mut _, Ordering::Relaxed);
+        ENVP.store(envp as *mut _, Ordering::Relaxed);
+        CHECKSUM.store(unsafe { checksum(argc, argv) }, Ordering::Relaxed);
+        ORIGINAL_ARGV.store(argv as *mut _, Ordering::Release);
+
+        // Take the copy that relocate() would make now, before any other
+        // constructor or main has had a chance to overwrite the original
+        // memory. The comparison of debug-check is left for later, because
+        // std::env::args_os may not be initialized yet.
+        #[cfg(feature = "snapshot")]
+        {
+            let _ = region();
+            copy();
+            STATE.store(RELOCATED, Ordering::Release);
         }
     }
 
+    // Checksum of the arguments ARGV points to, maintained by this crate
+    // across its own modifications, for detecting modifications by others.
+    static CHECKSUM: AtomicUsize = AtomicUsize::new(0);
+
+    // FNV-1a over every argument including its NUL terminator, truncated to
+    // usize because not every target has 64-bit atomics.
+    unsafe fn checksum(argc: c_int, argv: *const *const c_char) -> usize {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        if argv.is_null() {
+            return hash as usize;
+        }
+        for i in 0..argc as usize {
+            let ptr = unsafe { *argv.add(i) };
+            for &byte in unsafe { CStr::from_ptr(ptr) }.to_bytes_with_nul() {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash as usize
+    }
+
+    pub(crate) fn verify() -> bool {
+        let _guard = crate::lock::lock();
+        wait_for_relocation();
+        let argc = ARGC.load(Ordering::Relaxed);
+        let argv: *const *const c_char = ARGV.load(Ordering::Acquire);
+        unsafe { checksum(argc, argv) == CHECKSUM.load(Ordering::Relaxed) }
+    }
+
+    // Location of one argument in the memory the kernel placed it in,
+    // excluding its NUL terminator.
+    pub(crate) fn original_arg(index: usize) -> io::Result<Range<*mut u8>> {
+        let argc = ORIGINAL_ARGC.load(Ordering::Relaxed);
+        let argv = ORIGINAL_ARGV.load(Ordering::Acquire);
+        if argv.is_null() || index >= argc as usize {
+            return Err(crate::error::new(
+                io::ErrorKind::InvalidInput,
+                "argument index out of range",
+            ));
+        }
+        let ptr = unsafe { *argv.add(index) };
+        let len = unsafe { CStr::from_ptr(ptr) }.to_bytes().len();
+        let start = ptr as *mut u8;
+        Ok(start..unsafe { start.add(len) })
+    }
+
+    // Tracks whether references into the memory the kernel placed the
+    // arguments in have been handed out, and whether ARGV has been repointed
+    // at a private copy so that the original memory may be overwritten. Once
+    // relocated, the copy may itself be modified until the first reference
+    // into it is handed out. RELOCATING doubles as an exclusive lock on ARGV
+    // and the copy.
+    const PRISTINE: usize = 0;
+    const BORROWED: usize = 1;
+    const RELOCATING: usize = 2;
+    const RELOCATED: usize = 3;
+    const RELOCATED_BORROWED: usize = 4;
+    static STATE: AtomicUsize = AtomicUsize::new(PRISTINE);
+
+    #[derive(Copy, Clone)]
+    struct Region {
+        start: usize,
+        args_end: usize,
+        end: usize,
+    }
+
+    // Written once by REGION_ONCE. A start of 0 means there is no region.
+    static REGION_ONCE: Once = Once::new();
+    static REGION_START: AtomicUsize = AtomicUsize::new(0);
+    static REGION_ARGS_END: AtomicUsize = AtomicUsize::new(0);
+    static REGION_END: AtomicUsize = AtomicUsize::new(0);
+
+    fn region() -> Option<Region> {
+        REGION_ONCE.call_once(|| {
+            if let Some(region) = unsafe { compute_region() } {
+                REGION_START.store(region.start, Ordering::Relaxed);
+                REGION_ARGS_END.store(region.args_end, Ordering::Relaxed);
+                REGION_END.store(region.end, Ordering::Relaxed);
+            }
+        });
+        match REGION_START.load(Ordering::Relaxed) {
+            0 => None,
+            start => Some(Region {
+                start,
+                args_end: REGION_ARGS_END.load(Ordering::Relaxed),
+                end: REGION_END.load(Ordering::Relaxed),
+            }),
+        }
+    }
+
+    pub(crate) fn stack_region() -> Option<Range<*mut u8>> {
+        let region = region()?;
+        Some(region.start as *mut u8..region.end as *mut u8)
+    }
+
+    pub(crate) fn args_region() -> Option<Range<*mut u8>> {
+        let region = region()?;
+        Some(region.start as *mut u8..region.args_end as *mut u8)
+    }
+
+    unsafe fn compute_region() -> Option<Region> {
+        let argc = ARGC.load(Ordering::Relaxed);
+        let argv: *const *const c_char = ARGV.load(Ordering::Acquire);
+        let envp: *const *const c_char = ENVP.load(Ordering::Relaxed);
+        if argc <= 0 || argv.is_null() {
+            return None;
+        }
+
+        let start = unsafe { *argv };
+        if start.is_null() {
+            return None;
+        }
+
+        let mut end = start;
+        let mut contiguous = true;
+        for i in 0..argc as usize {
+            if !unsafe { extend(&mut end, *argv.add(i)) } {
+                contiguous = false;
+                break;
+            }
+        }
+        let args_end = end;
+        if contiguous && !envp.is_null() {
+            let mut env = envp;
+            loop {
+                let ptr = unsafe { *env };
+                if ptr.is_null() || !unsafe { extend(&mut end, ptr) } {
+                    break;
+                }
+                env = unsafe { env.add(1) };
+            }
+        }
+
+        Some(Region {
+            start: start as usize,
+            args_end: args_end as usize,
+            end: end as usize,
+        })
+    }
+
+    // Advances `end` past the string at `ptr` if that string starts exactly at
+    // `end`.
+    unsafe fn extend(end: &mut *const c_char, ptr: *const c_char) -> bool {
+        if ptr != *end {
+            return false;
+        }
+        let len = unsafe { CStr::from_ptr(ptr) }.to_bytes_with_nul().len();
+        *end = unsafe { ptr.add(len) };
+        true
+    }
+
+    fn wait_for_relocation() {
+        while STATE.load(Ordering::Acquire) == RELOCATING {
+            thread::yield_now();
+        }
+    }
+
+    fn borrowed() -> io::Error {
+        crate::error::new(
+            io::ErrorKind::Other,
+            "command line arguments are borrowed by argv::iter() and cannot be overwritten",
+        )
+    }
+
+    // Repoints ARGV at a private copy of the arguments, after which the memory
+    // the kernel placed them in is free to be overwritten. Fails if references
+    // into that memory have already been handed out by iter().
+    pub(crate) fn relocate() -> io::Result<()> {
+        match STATE.compare_exchange(PRISTINE, RELOCATING, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => {
+                if let Err(err) = crate::error::allocation() {
+                    STATE.store(PRISTINE, Ordering::Release);
+                    return Err(err);
+                }
+            }
+            Err(RELOCATING) => {
+                wait_for_relocation();
+                return Ok(());
+            }
+            Err(RELOCATED) => {
+                // Relocated by the constructor of the snapshot feature, which
+                // leaves the check to whichever comes first of this and
+                // borrow(), before this crate writes to the original memory.
+                #[cfg(feature = "debug-check")]
+                if let Some(mismatch) = debug_check() {
+                    mismatch.report();
+                }
+                return Ok(());
+            }
+            Err(RELOCATED_BORROWED) => return Ok(()),
+            Err(_) => return Err(borrowed()),
+        }
+
+        // Must be computed while the original memory is still intact.
+        let _ = region();
+        #[cfg(feature = "debug-check")]
+        let mismatch = debug_check();
+        copy();
+
+        STATE.store(RELOCATED, Ordering::Release);
+        #[cfg(feature = "debug-check")]
+        if let Some(mismatch) = mismatch {
+            mismatch.report();
+        }
+        Ok(())
+    }
+
+    // Copies the arguments ARGV points to into memory owned by this crate and
+    // repoints ARGV at the copy.
+    fn copy() {
+        let argc = ARGC.load(Ordering::Relaxed);
+        let argv: *const *const c_char = ARGV.load(Ordering::Acquire);
+        if argc > 0 && !argv.is_null() {
+            let mut bytes = Vec::new();
+            let mut offsets = Vec::with_capacity(argc as usize);
+            for i in 0..argc as usize {
+                let c_str = unsafe { CStr::from_ptr(*argv.add(i)) };
+                offsets.push(bytes.len());
+                bytes.extend_from_slice(c_str.to_bytes_with_nul());
+            }
+            // Only accessed through raw pointers, because take_from_copy() may
+            // write to it later.
+            let bytes = Box::leak(bytes.into_boxed_slice()).as_mut_ptr();
+            let mut ptrs: Vec<*const c_char> = offsets
+                .into_iter()
+                .map(|offset| unsafe { bytes.add(offset) } as *const c_char)
+                .collect();
+            ptrs.push(ptr::null());
+            // Likewise, because C code given the array by c_argv() may
+            // rearrange it before argv::refresh().
+            let ptrs = Box::leak(ptrs.into_boxed_slice()).as_mut_ptr();
+
+            #[cfg(target_env = "gnu")]
+            unsafe {
+                crate::invocation::relocate(*argv, *ptrs);
+            }
+
+            ARGV.store(ptrs, Ordering::Release);
+        }
+    }
+
+    // Passes the bytes of one argument in the private copy to `f`, then zeroes
+    // them if `f` succeeded so that iter() produces an empty string in its
+    // place. Fails if references into the copy have already been handed out by
+    // iter(). Must hold the lock.
+    pub(crate) fn take_from_copy<T>(
+        index: usize,
+        f: impl FnOnce(&[u8]) -> io::Result<T>,
+    ) -> io::Result<T> {
+        loop {
+            match STATE.compare_exchange(RELOCATED, RELOCATING, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(PRISTINE) => relocate()?,
+                Err(RELOCATING) => thread::yield_now(),
+                Err(_) => return Err(borrowed()),
+            }
+        }
+
+        let argc = ARGC.load(Ordering::Relaxed);
+        let result = if index < argc as usize {
+            let argv: *const *const c_char = ARGV.load(Ordering::Acquire);
+            let ptr = unsafe { *argv.add(index) } as *mut u8;
+            let len = unsafe { CStr::from_ptr(ptr.cast()) }.to_bytes().len();
+            let result = f(unsafe { slice::from_raw_parts(ptr, len) });
+            if result.is_ok() {
+                for i in 0..len {
+                    unsafe { ptr::write_volatile(ptr.add(i), 0) };
+                }
+                let checksum = unsafe { checksum(argc, argv) };
+                CHECKSUM.store(checksum, Ordering::Relaxed);
+            }
+            result
+        } else {
+            Err(crate::error::new(
+                io::ErrorKind::InvalidInput,
+                "argument index out of range",
+            ))
+        };
+
+        STATE.store(RELOCATED, Ordering::Release);
+        result
+    }
+
+    // Records that references into whatever ARGV points to are about to be
+    // handed out. Returns None instead of waiting if a relocation is in
+    // progress and `wait` is false.
+    fn borrow(wait: bool) -> Option<*const *const c_char> {
+        loop {
+            let (current, new) = match STATE.load(Ordering::Acquire) {
+                PRISTINE => (PRISTINE, BORROWED),
+                RELOCATED => (RELOCATED, RELOCATED_BORROWED),
+                RELOCATING if wait => {
+                    thread::yield_now();
+                    continue;
+                }
+                RELOCATING => return None,
+                _ => break,
+            };
+            if STATE
+                .compare_exchange_weak(current, new, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                #[cfg(feature = "debug-check")]
+                if current == PRISTINE || cfg!(feature = "snapshot") {
+                    if let Some(mismatch) = debug_check() {
+                        mismatch.report();
+                    }
+                }
+                break;
+            }
+        }
+        Some(ARGV.load(Ordering::Acquire))
+    }
+
+    // Compares the arguments ARGV points to against std::env::args_os, while
+    // the original memory is still intact.
+    #[cfg(feature = "debug-check")]
+    fn debug_check() -> Option<crate::debug_check::Mismatch> {
+        let argc = ARGC.load(Ordering::Relaxed);
+        let argv: *const *const c_char = ARGV.load(Ordering::Acquire);
+        let argc = if argv.is_null() { 0 } else { argc as usize };
+        let captured = (0..argc)
+            .map(|i| OsStr::from_bytes(unsafe { CStr::from_ptr(*argv.add(i)) }.to_bytes()));
+        crate::debug_check::compare(captured)
+    }
+
     pub(crate) fn iter() -> Iter {
-        // These are only mutated before main so they are safe to read once main
-        // has begun.
-        let argc = unsafe { ARGC };
-        let argv = unsafe { ARGV };
+        // ARGV may be repointed by relocate() and ARGC reduced by refresh(),
+        // which borrow() synchronizes with.
+        let argv = borrow(true).unwrap_or(ptr::null());
+        let argc = ARGC.load(Ordering::Relaxed);
 
         // We count on the OS to provide argv for which argv + argc does not
         // overflow.
@@ -115,6 +992,82 @@ mod r#impl {
         Iter { next: argv, end }
     }
 
+    // Whether the constructor received an argument array.
+    pub(crate) fn captured() -> bool {
+        !ORIGINAL_ARGV.load(Ordering::Acquire).is_null()
+    }
+
+    // The argument array passed by the kernel. Its pointers give the original
+    // positions of the arguments even after their memory was overwritten.
+    pub(crate) fn original_argv() -> (usize, *const *const c_char) {
+        let argv = ORIGINAL_ARGV.load(Ordering::Acquire);
+        if argv.is_null() {
+            return (0, ptr::null());
+        }
+        (ORIGINAL_ARGC.load(Ordering::Relaxed) as usize, argv)
+    }
+
+    // The argument array itself, for handing to C, which like every argv is
+    // terminated by a null pointer. Null if the arguments were not captured.
+    pub(crate) fn c_argv() -> (c_int, *const *const c_char) {
+        match borrow(true) {
+            Some(argv) if !argv.is_null() => (ARGC.load(Ordering::Relaxed), argv),
+            _ => (0, ptr::null()),
+        }
+    }
+
+    // Adopts changes that C code made to the argument array handed out by
+    // c_argv(), which removes arguments by moving the later pointers down and
+    // null-terminating the array early. The array cannot grow, so the new
+    // length is that of its prefix up to the first null pointer. Neither the
+    // array nor the strings move, so references produced earlier by iter()
+    // remain valid.
+    pub(crate) fn refresh() -> io::Result<()> {
+        let _guard = crate::lock::lock();
+        let previous = loop {
+            let state = STATE.load(Ordering::Acquire);
+            if state != RELOCATING
+                && STATE
+                    .compare_exchange_weak(state, RELOCATING, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+            {
+                break state;
+            }
+            thread::yield_now();
+        };
+
+        let argc = ARGC.load(Ordering::Relaxed);
+        let argv: *const *const c_char = ARGV.load(Ordering::Acquire);
+        if !argv.is_null() {
+            let argc = (0..argc)
+                .find(|&i| unsafe { *argv.add(i as usize) }.is_null())
+                .unwrap_or(argc);
+            ARGC.store(argc, Ordering::Relaxed);
+            CHECKSUM.store(unsafe { checksum(argc, argv) }, Ordering::Relaxed);
+        }
+
+        STATE.store(previous, Ordering::Release);
+        Ok(())
+    }
+
+    // Only atomic operations and plain memory reads, so that this is usable
+    // from a signal handler. Empty if the handler interrupted a thread in the
+    // middle of relocating the arguments.
+    pub(crate) fn raw_iter() -> Iter {
+        match borrow(false) {
+            Some(argv) => {
+                let argc = ARGC.load(Ordering::Relaxed);
+                let end = unsafe { argv.offset(argc as isize) };
+                Iter { next: argv, end }
+            }
+            None => Iter {
+                next: ptr::null(),
+                end: ptr::null(),
+            },
+        }
+    }
+
+    #[derive(Clone)]
     pub(crate) struct Iter {
         next: *const *const c_char,
         end: *const *const c_char,
@@ -128,8 +1081,14 @@ mod r#impl {
                 None
             } else {
                 let ptr = unsafe { *self.next };
-                let c_str = unsafe { CStr::from_ptr(ptr) };
                 self.next = unsafe { self.next.offset(1) };
+                // An iterator created before argv::refresh() shortened the
+                // array keeps its length, so that it remains exact, and
+                // produces an empty string past the new end.
+                if ptr.is_null() {
+                    return Some(OsStr::new(""));
+                }
+                let c_str = unsafe { CStr::from_ptr(ptr) };
                 Some(OsStr::from_bytes(c_str.to_bytes()))
             }
         }
@@ -151,27 +1110,402 @@ mod r#impl {
     unsafe impl Sync for Iter {}
 }
 
-#[cfg(any(not(target_os = "linux"), target_env = "musl"))]
+#[cfg(any(
+    not(target_os = "linux"),
+    target_env = "musl",
+    miri,
+    feature = "force-fallback",
+))]
 mod r#impl {
+    use std::borrow::Cow;
     use std::ffi::OsStr;
+    use std::io;
+    use std::ops::Range;
+    use std::os::raw::{c_char, c_int};
+    use std::ptr;
+    use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
     use std::sync::Once;
-    use std::{env, iter, ptr, slice};
 
+    use crate::source::Source;
+
+    // Without a constructor, glibc still allows finding the arguments in
+    // place, which avoids copying them.
+    #[cfg(all(
+        target_os = "linux",
+        target_env = "gnu",
+        not(miri),
+        feature = "no-ctor",
+    ))]
+    pub(crate) const SOURCE: Source = Source::Libc;
+    #[cfg(not(all(
+        target_os = "linux",
+        target_env = "gnu",
+        not(miri),
+        feature = "no-ctor",
+    )))]
+    pub(crate) const SOURCE: Source = Source::Std;
+
+    // Number of arguments, written once by ONCE after the storage.
     static ONCE: Once = Once::new();
-    static mut ARGV: Vec<&'static OsStr> = Vec::new();
+    static ARGC: AtomicUsize = AtomicUsize::new(0);
 
     pub(crate) fn iter() -> Iter {
         ONCE.call_once(|| {
-            let argv = env::args_os()
+            let argc = crate::zero_alloc::exempt(storage::init);
+            ARGC.store(argc, Ordering::Relaxed);
+        });
+        raw_iter()
+    }
+
+    #[derive(Clone)]
+    pub(crate) struct Iter {
+        next: usize,
+        end: usize,
+    }
+
+    impl Iterator for Iter {
+        type Item = &'static OsStr;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.next == self.end {
+                None
+            } else {
+                let arg = storage::get(self.next);
+                self.next += 1;
+                Some(arg)
+            }
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            let len = self.len();
+            (len, Some(len))
+        }
+    }
+
+    impl ExactSizeIterator for Iter {
+        fn len(&self) -> usize {
+            self.end - self.next
+        }
+    }
+
+    // Every argument followed by a NUL, in one buffer, and a table of the
+    // offset at which each argument begins followed by the length of the
+    // buffer.
+    #[cfg(unix)]
+    mod storage {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::ptr;
+        use std::slice;
+        use std::sync::atomic::{AtomicPtr, Ordering};
+
+        use crate::source::Source;
+
+        static BUFFER: AtomicPtr<u8> = AtomicPtr::new(ptr::null_mut());
+        static OFFSETS: AtomicPtr<usize> = AtomicPtr::new(ptr::null_mut());
+
+        pub(super) fn init() -> usize {
+            let (buffer, offsets) = match super::SOURCE {
+                Source::Libc if !cfg!(feature = "snapshot") => {
+                    let args = crate::source::libc();
+                    contiguous(&args).unwrap_or_else(|| copy(args))
+                }
+                Source::Libc => copy(crate::source::libc()),
+                _ => copy(std::env::args_os()),
+            };
+            let offsets = Box::leak(offsets.into_boxed_slice());
+            BUFFER.store(buffer as *mut u8, Ordering::Relaxed);
+            OFFSETS.store(offsets.as_mut_ptr(), Ordering::Relaxed);
+            offsets.len() - 1
+        }
+
+        pub(super) fn get(index: usize) -> &'static OsStr {
+            let buffer = BUFFER.load(Ordering::Relaxed);
+            let offsets = OFFSETS.load(Ordering::Relaxed);
+            let start = unsafe { *offsets.add(index) };
+            let end = unsafe { *offsets.add(index + 1) } - 1;
+            OsStr::from_bytes(unsafe { slice::from_raw_parts(buffer.add(start), end - start) })
+        }
+
+        fn copy<I>(args: I) -> (*const u8, Vec<usize>)
+        where
+            I: IntoIterator,
+            I::Item: AsRef<OsStr>,
+        {
+            let mut buffer = Vec::new();
+            let mut offsets = Vec::new();
+            for arg in args {
+                offsets.push(buffer.len());
+                buffer.extend_from_slice(arg.as_ref().as_bytes());
+                buffer.push(0);
+            }
+            offsets.push(buffer.len());
+            (Box::leak(buffer.into_boxed_slice()).as_ptr(), offsets)
+        }
+
+        // Borrows the memory the arguments are already in if, as placed by the
+        // kernel, each immediately follows the previous one's NUL terminator.
+        fn contiguous(args: &[&'static OsStr]) -> Option<(*const u8, Vec<usize>)> {
+            let start = args.first()?.as_bytes().as_ptr() as usize;
+            let mut offsets = Vec::with_capacity(args.len() + 1);
+            let mut end = start;
+            for arg in args {
+                let arg = arg.as_bytes();
+                if arg.as_ptr() as usize != end {
+                    return None;
+                }
+                offsets.push(end - start);
+                end += arg.len() + 1;
+            }
+            offsets.push(end - start);
+            Some((start as *const u8, offsets))
+        }
+    }
+
+    // Each argument is converted from the UTF-16 command line the first time
+    // it is accessed, so that a program which only looks at argv[1] does not
+    // pay for converting every other argument. The slot for each index holds
+    // the converted argument once there is one.
+    #[cfg(windows)]
+    mod storage {
+        use std::ffi::{OsStr, OsString};
+        use std::ops::Range;
+        use std::os::windows::ffi::OsStringExt;
+        use std::ptr;
+        use std::sync::atomic::{AtomicPtr, Ordering};
+
+        static CMDLINE: AtomicPtr<u16> = AtomicPtr::new(ptr::null_mut());
+        static RANGES: AtomicPtr<Range<usize>> = AtomicPtr::new(ptr::null_mut());
+        static SLOTS: AtomicPtr<AtomicPtr<Box<OsStr>>> = AtomicPtr::new(ptr::null_mut());
+
+        const QUOTE: u16 = b'"' as u16;
+        const BACKSLASH: u16 = b'\\' as u16;
+
+        pub(super) fn init() -> usize {
+            let cmdline = crate::spans::command_line().unwrap_or(&[]);
+            let ranges: Vec<Range<usize>> = crate::spans::parse(cmdline)
+                .iter()
+                .map(crate::Span::range)
+                .collect();
+            let slots: Vec<AtomicPtr<Box<OsStr>>> = if ranges.is_empty() {
+                // With an empty command line the standard library produces
+                // the path of the executable as the program name.
+                std::env::args_os()
+                    .map(|arg| AtomicPtr::new(Box::into_raw(Box::new(arg.into_boxed_os_str()))))
+                    .collect()
+            } else {
+                ranges.iter().map(|_| AtomicPtr::default()).collect()
+            };
+            let ranges = Box::leak(ranges.into_boxed_slice());
+            let slots = Box::leak(slots.into_boxed_slice());
+            CMDLINE.store(cmdline.as_ptr() as *mut u16, Ordering::Relaxed);
+            RANGES.store(ranges.as_mut_ptr(), Ordering::Relaxed);
+            SLOTS.store(slots.as_mut_ptr(), Ordering::Relaxed);
+            slots.len()
+        }
+
+        pub(super) fn get(index: usize) -> &'static OsStr {
+            let slot = unsafe { &*SLOTS.load(Ordering::Relaxed).add(index) };
+            let mut arg = slot.load(Ordering::Acquire);
+            if arg.is_null() {
+                let converted = crate::zero_alloc::exempt(|| {
+                    let range = unsafe { &*RANGES.load(Ordering::Relaxed).add(index) };
+                    let cmdline = CMDLINE.load(Ordering::Relaxed);
+                    let token = unsafe {
+                        std::slice::from_raw_parts(cmdline.add(range.start), range.len())
+                    };
+                    let arg = OsString::from_wide(&unescape(token, index == 0));
+                    Box::into_raw(Box::new(arg.into_boxed_os_str()))
+                });
+                // Another thread may have converted the same argument in the
+                // meantime, in which case its conversion is the one kept.
+                arg = match slot.compare_exchange(
+                    ptr::null_mut(),
+                    converted,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => converted,
+                    Err(existing) => {
+                        drop(unsafe { Box::from_raw(converted) });
+                        existing
+                    }
+                };
+            }
+            unsafe { &*arg }
+        }
+
+        // Removes the quoting from one argument as delimited by
+        // crate::spans::parse. The program name has no escapes, only quotes.
+        fn unescape(token: &[u16], program: bool) -> Vec<u16> {
+            let mut arg = Vec::with_capacity(token.len());
+            let mut in_quotes = false;
+            let mut i = 0;
+            while i < token.len() {
+                match token[i] {
+                    QUOTE if program => {}
+                    BACKSLASH if !program => {
+                        let mut count = 0;
+                        while i < token.len() && token[i] == BACKSLASH {
+                            count += 1;
+                            i += 1;
+                        }
+                        if token.get(i) == Some(&QUOTE) {
+                            arg.resize(arg.len() + count / 2, BACKSLASH);
+                            if count % 2 == 1 {
+                                arg.push(QUOTE);
+                                i += 1;
+                            }
+                        } else {
+                            arg.resize(arg.len() + count, BACKSLASH);
+                        }
+                        continue;
+                    }
+                    QUOTE if in_quotes && token.get(i + 1) == Some(&QUOTE) => {
+                        arg.push(QUOTE);
+                        i += 1;
+                    }
+                    QUOTE => in_quotes = !in_quotes,
+                    unit => arg.push(unit),
+                }
+                i += 1;
+            }
+            arg
+        }
+    }
+
+    // OsStr cannot be constructed from bytes on other platforms on the
+    // minimum supported compiler, so each argument is leaked individually.
+    #[cfg(not(any(unix, windows)))]
+    mod storage {
+        use std::ffi::OsStr;
+        use std::ptr;
+        use std::sync::atomic::{AtomicPtr, Ordering};
+
+        static ARGV: AtomicPtr<&'static OsStr> = AtomicPtr::new(ptr::null_mut());
+
+        pub(super) fn init() -> usize {
+            let argv: Vec<&'static OsStr> = std::env::args_os()
                 .map(|arg| -> &OsStr { Box::leak(arg.into_boxed_os_str()) })
                 .collect();
-            unsafe { ARGV = argv }
+            let argv = Box::leak(argv.into_boxed_slice());
+            ARGV.store(argv.as_mut_ptr(), Ordering::Relaxed);
+            argv.len()
+        }
+
+        pub(super) fn get(index: usize) -> &'static OsStr {
+            unsafe { *ARGV.load(Ordering::Relaxed).add(index) }
+        }
+    }
+
+    pub(crate) fn captured() -> bool {
+        iter().len() > 0
+    }
+
+    // Allocation would not be async-signal-safe, so nothing is produced until
+    // some earlier call to iter() has collected the arguments.
+    pub(crate) fn raw_iter() -> Iter {
+        let end = if ONCE.is_completed() {
+            ARGC.load(Ordering::Relaxed)
+        } else {
+            0
+        };
+        Iter { next: 0, end }
+    }
+
+    // C has no use for OsStr, so the arguments are copied once more into
+    // NUL-terminated strings with a null-terminated array of pointers to them.
+    pub(crate) fn c_argv() -> (c_int, *const *const c_char) {
+        static C_ONCE: Once = Once::new();
+        static C_ARGV: AtomicPtr<*const c_char> = AtomicPtr::new(ptr::null_mut());
+        static C_ARGC: AtomicUsize = AtomicUsize::new(0);
+
+        C_ONCE.call_once(|| {
+            let c_argv = crate::zero_alloc::exempt(|| {
+                let mut c_argv: Vec<*const c_char> = iter()
+                    .map(|arg| {
+                        let mut bytes = bytes(arg).into_owned();
+                        bytes.push(0);
+                        Box::leak(bytes.into_boxed_slice()).as_ptr().cast()
+                    })
+                    .collect();
+                c_argv.push(ptr::null());
+                c_argv
+            });
+            C_ARGC.store(c_argv.len() - 1, Ordering::Relaxed);
+            C_ARGV.store(
+                Box::leak(c_argv.into_boxed_slice()).as_mut_ptr(),
+                Ordering::Relaxed,
+            );
         });
-        let argv = unsafe { &*ptr::addr_of!(ARGV) };
-        argv.iter().copied()
+        (
+            C_ARGC.load(Ordering::Relaxed) as c_int,
+            C_ARGV.load(Ordering::Relaxed),
+        )
+    }
+
+    #[cfg(unix)]
+    fn bytes(arg: &OsStr) -> Cow<'_, [u8]> {
+        use std::os::unix::ffi::OsStrExt;
+
+        Cow::Borrowed(arg.as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    fn bytes(arg: &OsStr) -> Cow<'_, [u8]> {
+        match arg.to_string_lossy() {
+            Cow::Borrowed(string) => Cow::Borrowed(string.as_bytes()),
+            Cow::Owned(string) => Cow::Owned(string.into_bytes()),
+        }
     }
 
-    pub(crate) type Iter = iter::Copied<slice::Iter<'static, &'static OsStr>>;
+    pub(crate) fn stack_region() -> Option<Range<*mut u8>> {
+        None
+    }
+
+    pub(crate) fn args_region() -> Option<Range<*mut u8>> {
+        None
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn original_argv() -> (usize, *const *const c_char) {
+        (0, ptr::null())
+    }
+
+    // The arguments already live in memory owned by this crate.
+    pub(crate) fn relocate() -> io::Result<()> {
+        Ok(())
+    }
+
+    pub(crate) fn original_arg(_index: usize) -> io::Result<Range<*mut u8>> {
+        Err(crate::error::new(
+            io::ErrorKind::Other,
+            "the original argument memory is not accessible on this platform",
+        ))
+    }
+
+    // The arguments were copied out of whatever C code could have modified.
+    pub(crate) fn refresh() -> io::Result<()> {
+        Err(crate::error::new(
+            io::ErrorKind::Other,
+            "the argument array is not shared with C code on this platform",
+        ))
+    }
+
+    // The copy is owned by this crate and nothing else writes to it.
+    pub(crate) fn verify() -> bool {
+        true
+    }
+
+    pub(crate) fn take_from_copy<T>(
+        _index: usize,
+        _f: impl FnOnce(&[u8]) -> io::Result<T>,
+    ) -> io::Result<T> {
+        Err(crate::error::new(
+            io::ErrorKind::Other,
+            "taking an argument is not supported on this platform",
+        ))
+    }
 }
 
 const _AUTO_TRAITS: () = {
@@ -0,0 +1,96 @@
+//! Telling apart the arguments of the standard test harness.
+//!
+//! Code running inside `cargo test` sees the command line of the test binary,
+//! which holds the harness's own flags like `--nocapture` and
+//! `--test-threads=1` as well as test name filters.
+
+use std::ffi::OsStr;
+
+// Flags of the libtest harness that take no value.
+const SWITCHES: &[&str] = &[
+    "--bench",
+    "--ensure-time",
+    "--exact",
+    "--exclude-should-panic",
+    "--force-run-in-process",
+    "--help",
+    "--ignored",
+    "--include-ignored",
+    "--list",
+    "--no-capture",
+    "--nocapture",
+    "--quiet",
+    "--report-time",
+    "--show-output",
+    "--shuffle",
+    "--test",
+    "-h",
+    "-q",
+];
+
+// Flags of the libtest harness that take a value, either attached with `=`
+// or as the following argument.
+const OPTIONS: &[&str] = &[
+    "--color",
+    "--format",
+    "--logfile",
+    "--shuffle-seed",
+    "--skip",
+    "--test-threads",
+    "-Z",
+];
+
+/// Removes the arguments that belong to the libtest harness.
+///
+/// The first argument, the path of the test binary, is kept. After that, the
+/// harness's flags and their values are removed, and so are positional
+/// arguments, which the harness interprets as test name filters. What remains
+/// is any argument the harness does not know about.
+///
+/// # Example
+///
+/// ```
+/// let args = ["target/debug/deps/app-1a2b", "--nocapture", "--test-threads", "1", "parse"];
+/// assert_eq!(argv::libtest::strip(args.iter()), [&"target/debug/deps/app-1a2b"]);
+/// ```
+pub fn strip<I>(args: I) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let mut args = args.into_iter();
+    let mut kept = Vec::new();
+    kept.extend(args.next());
+    let mut takes_value = false;
+    for arg in args {
+        if takes_value {
+            takes_value = false;
+            continue;
+        }
+        let arg_str = if let Some(arg_str) = arg.as_ref().to_str() {
+            arg_str
+        } else {
+            kept.push(arg);
+            continue;
+        };
+        if !arg_str.starts_with('-') {
+            // A test name filter.
+            continue;
+        }
+        if SWITCHES.contains(&arg_str) {
+            continue;
+        }
+        if OPTIONS.contains(&arg_str) {
+            takes_value = true;
+            continue;
+        }
+        let attached = OPTIONS.iter().any(|option| {
+            arg_str.starts_with(option)
+                && (*option == "-Z" || arg_str[option.len()..].starts_with('='))
+        });
+        if !attached {
+            kept.push(arg);
+        }
+    }
+    kept
+}
@@ -0,0 +1,25 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+// Serializes writes into the memory the kernel placed the arguments in. A
+// spin lock because std's Mutex cannot be constructed in a static on the
+// minimum supported compiler.
+static LOCK: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn lock() -> Guard {
+    while LOCK
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        thread::yield_now();
+    }
+    Guard
+}
+
+pub(crate) struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        LOCK.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,85 @@
+//! Logging the command line once at startup through the [log] crate.
+//!
+//! Requires the `log` feature. Many services begin by logging how they were
+//! invoked, but the command line is available before the logger is, which
+//! leaves it to be done by hand after logging has been configured.
+//! [`StartupLogger`] wraps whatever logger the program uses and emits the
+//! command line as the first record that reaches it:
+//!
+//! ```text
+//! use argv::log::StartupLogger;
+//! use argv::redact::Redactor;
+//!
+//! let logger = env_logger::Builder::from_default_env().build();
+//! let max_level = logger.filter();
+//! log::set_boxed_logger(Box::new(StartupLogger::new(logger, &Redactor::with_defaults())))?;
+//! log::set_max_level(max_level);
+//! ```
+//!
+//! The record has level `Info` and target `argv`, and reads `command line:`
+//! followed by the shell-quoted, redacted arguments.
+//!
+//! [log]: https://docs.rs/log
+
+use crate::redact::Redactor;
+use ::log::{Level, Log, Metadata, Record};
+use std::sync::Once;
+
+const TARGET: &str = "argv";
+
+/// Logger that logs the command line before the first record passed to it.
+pub struct StartupLogger<L> {
+    inner: L,
+    command_line: String,
+    once: Once,
+}
+
+impl<L: Log> StartupLogger<L> {
+    /// Wraps `inner`, formatting the command line now with secret values
+    /// masked by `redactor`.
+    pub fn new(inner: L, redactor: &Redactor) -> Self {
+        StartupLogger {
+            inner,
+            command_line: redactor.display(crate::iter()).to_string(),
+            once: Once::new(),
+        }
+    }
+
+    /// The wrapped logger.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    fn log_command_line(&self) {
+        self.once.call_once(|| {
+            let metadata = Metadata::builder()
+                .level(Level::Info)
+                .target(TARGET)
+                .build();
+            if self.inner.enabled(&metadata) {
+                self.inner.log(
+                    &Record::builder()
+                        .args(format_args!("command line: {}", self.command_line))
+                        .level(Level::Info)
+                        .target(TARGET)
+                        .build(),
+                );
+            }
+        });
+    }
+}
+
+impl<L: Log> Log for StartupLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.log_command_line();
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
@@ -0,0 +1,146 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
+/// Converts the command line arguments to strings lossily, recording which
+/// arguments had to be changed.
+///
+/// Ill-formed sequences are replaced by U+FFFD REPLACEMENT CHARACTER exactly
+/// as by `OsStr::to_string_lossy`. Arguments that are valid Unicode are
+/// borrowed. Unlike calling `to_string_lossy` on every argument, the result
+/// tells which arguments were changed, so that a tool can warn about them
+/// precisely instead of silently operating on mangled input.
+///
+/// # Example
+///
+/// ```
+/// let lossy = argv::lossy();
+/// for replacement in lossy.replacements() {
+///     eprintln!(
+///         "warning: argument {} is not valid UTF-8; {} invalid bytes were replaced",
+///         replacement.index(),
+///         replacement.count(),
+///     );
+/// }
+/// let args = lossy.into_args();
+/// # let _ = args;
+/// ```
+pub fn lossy() -> Lossy {
+    let mut iter = crate::iter();
+    let mut args = Vec::with_capacity(iter.len());
+    let mut replacements = Vec::new();
+    while let Some(arg) = iter.next_str() {
+        let (string, count) = match arg {
+            Ok(string) => (Cow::Borrowed(string), 0),
+            Err(arg) => convert(arg),
+        };
+        if count > 0 {
+            let index = args.len();
+            replacements.push(Replacement { index, count });
+        }
+        args.push(string);
+    }
+    Lossy { args, replacements }
+}
+
+/// Arguments converted by [`lossy`], with a record of the replacements made.
+#[derive(Clone, Debug)]
+pub struct Lossy {
+    args: Vec<Cow<'static, str>>,
+    replacements: Vec<Replacement>,
+}
+
+impl Lossy {
+    /// The converted arguments.
+    pub fn args(&self) -> &[Cow<'static, str>] {
+        &self.args
+    }
+
+    /// The arguments that were not valid Unicode, in order of index.
+    pub fn replacements(&self) -> &[Replacement] {
+        &self.replacements
+    }
+
+    /// Whether every argument was valid Unicode and no replacement was made.
+    pub fn is_lossless(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    /// Takes the converted arguments.
+    pub fn into_args(self) -> Vec<Cow<'static, str>> {
+        self.args
+    }
+}
+
+/// Replacements made in one argument by [`lossy`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Replacement {
+    index: usize,
+    count: usize,
+}
+
+impl Replacement {
+    /// Position of the argument in [`argv::iter()`][crate::iter].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Number of units of the original argument that were replaced: bytes on
+    /// Unix and UTF-16 code units on Windows. Other platforms report 1 for
+    /// every changed argument.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(unix)]
+fn convert(arg: &'static OsStr) -> (Cow<'static, str>, usize) {
+    use std::os::unix::ffi::OsStrExt;
+    use std::str;
+
+    let mut rest = arg.as_bytes();
+    let mut string = String::new();
+    let mut count = 0;
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) if count == 0 => return (Cow::Borrowed(valid), 0),
+            Ok(valid) => {
+                string.push_str(valid);
+                return (Cow::Owned(string), count);
+            }
+            Err(err) => {
+                let (valid, after) = rest.split_at(err.valid_up_to());
+                string.push_str(str::from_utf8(valid).unwrap());
+                string.push(char::REPLACEMENT_CHARACTER);
+                let invalid = err.error_len().unwrap_or(after.len());
+                count += invalid;
+                rest = &after[invalid..];
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+fn convert(arg: &'static OsStr) -> (Cow<'static, str>, usize) {
+    use std::os::windows::ffi::OsStrExt;
+
+    if let Some(string) = arg.to_str() {
+        return (Cow::Borrowed(string), 0);
+    }
+    let mut string = String::with_capacity(arg.len());
+    let mut count = 0;
+    for ch in std::char::decode_utf16(arg.encode_wide()) {
+        string.push(ch.unwrap_or_else(|_| {
+            count += 1;
+            char::REPLACEMENT_CHARACTER
+        }));
+    }
+    (Cow::Owned(string), count)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn convert(arg: &'static OsStr) -> (Cow<'static, str>, usize) {
+    match arg.to_string_lossy() {
+        Cow::Borrowed(string) => (Cow::Borrowed(string), 0),
+        Cow::Owned(string) => (Cow::Owned(string), 1),
+    }
+}
@@ -0,0 +1,16 @@
+// Types that #[argv::main] can pass to main.
+pub trait MainArgs {
+    fn main_args() -> Self;
+}
+
+impl MainArgs for crate::Args {
+    fn main_args() -> Self {
+        crate::args()
+    }
+}
+
+impl MainArgs for crate::Iter {
+    fn main_args() -> Self {
+        crate::iter()
+    }
+}
@@ -0,0 +1,309 @@
+//! Overriding the arguments seen by [`argv::iter()`][crate::iter] in tests.
+//!
+//! Requires the `mock` feature, which is intended to be enabled only in
+//! dev-dependencies:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! argv = { version = "0.1", features = ["mock"] }
+//! ```
+//!
+//! While an override is in effect, `argv::iter()` and everything built on it,
+//! such as [`argv::program()`][crate::program], produce the overriding
+//! arguments. [`argv::raw_iter()`][crate::raw_iter] and the functions that
+//! operate on the original argument memory are unaffected.
+//!
+//! # Environment variable
+//!
+//! If the `ARGV_OVERRIDE` environment variable is set when `argv::iter()` is
+//! first called, its contents replace the real command line for the rest of
+//! the process, below any override installed through this module. Arguments
+//! are separated by NUL characters if there are any, and by newlines
+//! otherwise. This lets black-box tests and fuzzers drive a program's argument
+//! handling without an exec wrapper for every case:
+//!
+//! ```console
+//! $ ARGV_OVERRIDE=$'prog\n--flag\nvalue' ./target/debug/prog
+//! ```
+//!
+//! # Leaks
+//!
+//! The arguments are leaked in order to hand out `&'static OsStr` from
+//! `argv::iter()`, which is fine for tests but makes this unsuitable for
+//! overriding the arguments repeatedly in a long-running program.
+
+use std::cell::Cell;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::Once;
+use std::thread;
+
+type Args = &'static [&'static OsStr];
+
+// Every set of overriding arguments ever installed, kept reachable so that
+// leak checkers such as Miri's do not report them.
+struct Leaked {
+    args: Args,
+    next: *mut Leaked,
+}
+
+static LEAKED: AtomicPtr<Leaked> = AtomicPtr::new(ptr::null_mut());
+static OVERRIDE: AtomicPtr<Leaked> = AtomicPtr::new(ptr::null_mut());
+static SCOPE_LOCK: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static IN_SCOPE: Cell<bool> = Cell::new(false);
+    static THREAD_OVERRIDE: Cell<Option<Args>> = Cell::new(None);
+}
+
+/// Runs `f` with `argv::iter()` producing `args` instead of the real command
+/// line, then restores the previous arguments.
+///
+/// The override is process-global, so calls from concurrently running tests
+/// take turns rather than observe each other's arguments. Calls may be nested
+/// within one thread.
+///
+/// # Example
+///
+/// ```
+/// fn verbose() -> bool {
+///     argv::iter().any(|arg| arg == "--verbose")
+/// }
+///
+/// argv::mock::with_args(&["prog", "--verbose"], || {
+///     assert!(verbose());
+/// });
+/// ```
+pub fn with_args<I, R>(args: I, f: impl FnOnce() -> R) -> R
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let _scope = Scope::enter();
+    let _restore = Restore {
+        previous: OVERRIDE.swap(leak(args), Ordering::AcqRel),
+    };
+    f()
+}
+
+/// Runs `f` with `argv::iter()` producing `args` on the current thread only,
+/// then restores the previous arguments.
+///
+/// A thread-local override takes precedence over [`with_args`] and
+/// [`set_override`]. Unlike `with_args`, tests that use this do not take
+/// turns, so parallel tests can each simulate a different command line.
+/// Threads spawned by `f` see the process-global arguments.
+///
+/// # Example
+///
+/// ```
+/// argv::mock::with_thread_args(&["prog", "--jobs", "4"], || {
+///     assert_eq!(argv::iter().len(), 3);
+/// });
+/// ```
+pub fn with_thread_args<I, R>(args: I, f: impl FnOnce() -> R) -> R
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let args = unsafe { (*leak(args)).args };
+    let _restore = RestoreThread {
+        previous: THREAD_OVERRIDE.with(|thread_override| thread_override.replace(Some(args))),
+    };
+    f()
+}
+
+/// Makes `argv::iter()` produce `args` instead of the real command line from
+/// now on, for every thread.
+///
+/// Meant to be called once from test setup, so that all library code that
+/// reads the arguments sees a synthetic command line. Any [`with_args`] scope
+/// that is active at the time will restore the arguments it replaced when it
+/// ends.
+///
+/// # Example
+///
+/// ```
+/// argv::mock::set_override(&["prog", "--config", "test.toml"]);
+/// assert_eq!(argv::iter().nth(2).unwrap(), "test.toml");
+///
+/// argv::mock::clear_override();
+/// ```
+pub fn set_override<I>(args: I)
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    OVERRIDE.store(leak(args), Ordering::Release);
+}
+
+/// Removes an override installed by [`set_override`], so that
+/// `argv::iter()` produces the real command line again.
+pub fn clear_override() {
+    OVERRIDE.store(ptr::null_mut(), Ordering::Release);
+}
+
+/// Command line recorded by serializing [`argv::iter()`][crate::iter], loaded
+/// back for replaying in a test.
+///
+/// Requires the `serde` feature in addition to `mock`. Deserializes from the
+/// format produced by the `Serialize` impl of [`Iter`][crate::Iter], so that an
+/// invocation captured in a log or bug report can be fed to the application
+/// exactly as it originally appeared.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Invocation {
+    pub(crate) args: Vec<OsString>,
+}
+
+#[cfg(feature = "serde")]
+impl Invocation {
+    /// The recorded arguments.
+    pub fn args(&self) -> &[OsString] {
+        &self.args
+    }
+
+    /// Makes `argv::iter()` produce the recorded arguments from now on, for
+    /// every thread. Equivalent to passing them to [`set_override`].
+    pub fn install_as_override(&self) {
+        set_override(&self.args);
+    }
+}
+
+pub(crate) fn current() -> Option<Args> {
+    if let Some(args) = THREAD_OVERRIDE.with(Cell::get) {
+        return Some(args);
+    }
+    let leaked = OVERRIDE.load(Ordering::Acquire);
+    if !leaked.is_null() {
+        return Some(unsafe { (*leaked).args });
+    }
+    if let Some(args) = crate::fuzz::current() {
+        return Some(args);
+    }
+    env_override()
+}
+
+fn env_override() -> Option<Args> {
+    static ONCE: Once = Once::new();
+    static ENV_OVERRIDE: AtomicPtr<Leaked> = AtomicPtr::new(ptr::null_mut());
+
+    ONCE.call_once(|| {
+        let leaked = crate::zero_alloc::exempt(|| {
+            let value = env::var_os("ARGV_OVERRIDE")?;
+            Some(leak(split(&value)))
+        });
+        if let Some(leaked) = leaked {
+            ENV_OVERRIDE.store(leaked, Ordering::Release);
+        }
+    });
+    let leaked = ENV_OVERRIDE.load(Ordering::Acquire);
+    if leaked.is_null() {
+        None
+    } else {
+        Some(unsafe { (*leaked).args })
+    }
+}
+
+// Splits on NUL if present, otherwise on newline, ignoring one trailing
+// separator.
+#[cfg(unix)]
+fn split(value: &OsStr) -> Vec<OsString> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let bytes = value.as_bytes();
+    let separator = if bytes.contains(&0) { 0 } else { b'\n' };
+    let bytes = bytes.strip_suffix(&[separator]).unwrap_or(bytes);
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+    bytes
+        .split(|&b| b == separator)
+        .map(|arg| OsStr::from_bytes(arg).to_owned())
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn split(value: &OsStr) -> Vec<OsString> {
+    let value = value.to_string_lossy();
+    let separator = if value.contains('\0') { '\0' } else { '\n' };
+    let value = value.strip_suffix(separator).unwrap_or(&value);
+    if value.is_empty() {
+        return Vec::new();
+    }
+    value.split(separator).map(OsString::from).collect()
+}
+
+fn leak<I>(args: I) -> *mut Leaked
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let args: Vec<&'static OsStr> = args
+        .into_iter()
+        .map(|arg| -> &OsStr { Box::leak(Box::from(arg.as_ref())) })
+        .collect();
+    let leaked = Box::into_raw(Box::new(Leaked {
+        args: Box::leak(args.into_boxed_slice()),
+        next: LEAKED.load(Ordering::Relaxed),
+    }));
+    loop {
+        let next = unsafe { (*leaked).next };
+        match LEAKED.compare_exchange_weak(next, leaked, Ordering::AcqRel, Ordering::Relaxed) {
+            Ok(_) => return leaked,
+            Err(actual) => unsafe { (*leaked).next = actual },
+        }
+    }
+}
+
+struct Scope {
+    outermost: bool,
+}
+
+impl Scope {
+    fn enter() -> Self {
+        let outermost = !IN_SCOPE.with(|in_scope| in_scope.replace(true));
+        if outermost {
+            while SCOPE_LOCK
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                thread::yield_now();
+            }
+        }
+        Scope { outermost }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if self.outermost {
+            SCOPE_LOCK.store(false, Ordering::Release);
+            IN_SCOPE.with(|in_scope| in_scope.set(false));
+        }
+    }
+}
+
+struct Restore {
+    previous: *mut Leaked,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        // The overriding arguments are leaked, not freed, because iterators
+        // over them may outlive the scope.
+        OVERRIDE.store(self.previous, Ordering::Release);
+    }
+}
+
+struct RestoreThread {
+    previous: Option<Args>,
+}
+
+impl Drop for RestoreThread {
+    fn drop(&mut self) {
+        THREAD_OVERRIDE.with(|thread_override| thread_override.set(self.previous));
+    }
+}
@@ -0,0 +1,81 @@
+//! Lending the command line to `MPI_Init`.
+//!
+//! MPI implementations take `int *argc, char ***argv` and may remove the
+//! arguments meant for the MPI runtime, so that the program sees only its
+//! own. [`init`] provides a mutable copy of the command line in that shape,
+//! and afterward makes [`argv::iter()`][crate::iter] produce whatever MPI left
+//! in it.
+//!
+//! ```
+//! use std::os::raw::{c_char, c_int};
+//!
+//! # unsafe extern "C" fn MPI_Init(_argc: *mut c_int, _argv: *mut *mut *mut c_char) -> c_int { 0 }
+//! # /*
+//! extern "C" {
+//!     fn MPI_Init(argc: *mut c_int, argv: *mut *mut *mut c_char) -> c_int;
+//! }
+//! # */
+//!
+//! let rc = argv::mpi::init(|argc, argv| unsafe { MPI_Init(argc, argv) }).unwrap();
+//! assert_eq!(rc, 0);
+//! ```
+
+use std::ffi::{CStr, OsStr};
+use std::io;
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+use std::ptr;
+
+/// Calls `f` with pointers to `argc` and `argv` holding a copy of the command
+/// line, then installs the arguments that `f` left there as the ones produced
+/// by [`argv::iter()`][crate::iter] for the rest of the program.
+///
+/// The arguments are installed through
+/// [`argv::embed::set_logical_args`][crate::embed::set_logical_args], so this
+/// can be done only once. The copy lent to `f` is never freed, because MPI
+/// implementations may hold on to the pointers.
+///
+/// # Errors
+///
+/// Fails without calling `f` if logical arguments have already been
+/// installed, or after calling `f` if they were installed concurrently.
+pub fn init<R>(f: impl FnOnce(*mut c_int, *mut *mut *mut c_char) -> R) -> io::Result<R> {
+    if crate::embed::current().is_some() {
+        return Err(already_set());
+    }
+
+    let mut ptrs: Vec<*mut c_char> = crate::iter()
+        .map(|arg| {
+            let mut bytes = Vec::with_capacity(arg.len() + 1);
+            bytes.extend_from_slice(arg.as_bytes());
+            bytes.push(0);
+            Box::leak(bytes.into_boxed_slice()).as_mut_ptr().cast()
+        })
+        .collect();
+    let mut argc = ptrs.len() as c_int;
+    ptrs.push(ptr::null_mut());
+    let mut argv = Box::leak(ptrs.into_boxed_slice()).as_mut_ptr();
+
+    let result = f(&mut argc, &mut argv);
+
+    let mut args = Vec::new();
+    if !argv.is_null() {
+        for i in 0..argc.max(0) as usize {
+            let arg = unsafe { *argv.add(i) };
+            if arg.is_null() {
+                break;
+            }
+            let arg = unsafe { CStr::from_ptr(arg) };
+            args.push(OsStr::from_bytes(arg.to_bytes()));
+        }
+    }
+    crate::embed::set_logical_args(args).map_err(|_| already_set())?;
+    Ok(result)
+}
+
+fn already_set() -> io::Error {
+    crate::error::new(
+        io::ErrorKind::AlreadyExists,
+        "logical arguments have already been set",
+    )
+}
@@ -0,0 +1,62 @@
+//! Command line arguments in Unicode Normalization Form C.
+//!
+//! Requires the `unicode-normalization` feature. The same text can be
+//! represented by different sequences of code points: `é` either as the
+//! single code point U+00E9 or as `e` followed by U+0301 COMBINING ACUTE
+//! ACCENT. macOS file dialogs and some terminals pass the decomposed form,
+//! which then fails to compare equal to the composed form used elsewhere.
+//! Normalizing the arguments to NFC makes such comparisons behave.
+//!
+//! ```
+//! for arg in argv::nfc::iter() {
+//!     println!("{}", arg.to_string_lossy());
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Returns an iterator over command line arguments normalized to NFC.
+///
+/// Arguments that are already in NFC are borrowed. Arguments that are not
+/// valid Unicode are produced unchanged.
+pub fn iter() -> Iter {
+    Iter {
+        inner: crate::iter(),
+    }
+}
+
+/// Iterator over NFC-normalized command line arguments, returned by [`iter`].
+#[derive(Clone)]
+pub struct Iter {
+    inner: crate::Iter,
+}
+
+impl Iterator for Iter {
+    type Item = Cow<'static, OsStr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_str().map(normalize)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+fn normalize(arg: Result<&'static str, &'static OsStr>) -> Cow<'static, OsStr> {
+    match arg {
+        Ok(string) if !is_nfc(string) => {
+            Cow::Owned(OsString::from(string.nfc().collect::<String>()))
+        }
+        Ok(string) => Cow::Borrowed(OsStr::new(string)),
+        Err(arg) => Cow::Borrowed(arg),
+    }
+}
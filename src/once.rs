@@ -0,0 +1,44 @@
+use std::cell::UnsafeCell;
+use std::sync::Once;
+
+// A value computed at most once and then shared for the rest of the program,
+// for statics that would otherwise be a `static mut` next to a Once. The
+// standard library's OnceCell and OnceLock are newer than the minimum
+// supported compiler.
+pub(crate) struct OnceCell<T> {
+    once: Once,
+    value: UnsafeCell<Option<T>>,
+}
+
+// The value is written only inside call_once, and read only after call_once
+// has returned or is_completed has observed its completion, both of which
+// synchronize with the write. It is never written again, so shared
+// references handed out to any thread never alias a mutable one.
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> {}
+
+impl<T> OnceCell<T> {
+    pub(crate) const fn new() -> Self {
+        OnceCell {
+            once: Once::new(),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    pub(crate) fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        self.once.call_once(|| {
+            let value = f();
+            unsafe { *self.value.get() = Some(value) };
+        });
+        self.get().unwrap()
+    }
+
+    // Performs only an atomic load, so it is safe to call from a signal
+    // handler.
+    pub(crate) fn get(&self) -> Option<&T> {
+        if self.once.is_completed() {
+            unsafe { (*self.value.get()).as_ref() }
+        } else {
+            None
+        }
+    }
+}
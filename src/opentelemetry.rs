@@ -0,0 +1,39 @@
+//! OpenTelemetry resource attributes describing the process.
+//!
+//! Requires the `opentelemetry` feature.
+
+use crate::redact::Redactor;
+use ::opentelemetry::{Array, KeyValue, StringValue};
+use std::env;
+
+/// Returns the `process.command`, `process.command_args` and
+/// `process.executable.path` resource attributes defined by the OpenTelemetry
+/// semantic conventions.
+///
+/// `process.command` is [`argv::program()`][crate::program] and
+/// `process.command_args` holds every argument including the program name,
+/// with secret values masked by `redactor`. `process.executable.path` comes
+/// from `std::env::current_exe()` and is left out if that fails. Arguments
+/// that are not valid UTF-8 are converted lossily.
+///
+/// ```text
+/// let resource = Resource::new(argv::opentelemetry::resource_attributes(&Redactor::with_defaults()));
+/// ```
+pub fn resource_attributes(redactor: &Redactor) -> Vec<KeyValue> {
+    let command = crate::program().to_string_lossy().into_owned();
+    let command_args: Vec<StringValue> = redactor
+        .redact(crate::iter())
+        .iter()
+        .map(|arg| StringValue::from(arg.to_string_lossy().into_owned()))
+        .collect();
+
+    let mut attributes = vec![
+        KeyValue::new("process.command", command),
+        KeyValue::new("process.command_args", Array::String(command_args)),
+    ];
+    if let Ok(path) = env::current_exe() {
+        let path = path.to_string_lossy().into_owned();
+        attributes.push(KeyValue::new("process.executable.path", path));
+    }
+    attributes
+}
@@ -0,0 +1,36 @@
+use crate::redact::Redactor;
+use std::io::{self, Write};
+use std::panic;
+
+/// Installs a panic hook that follows the panic message with the command line
+/// the program was run with.
+///
+/// The hook that was installed before, normally the standard library's, still
+/// runs first, so the usual message and backtrace are unchanged. After it, a
+/// line like `command line: prog --token '***' input.txt` is written to
+/// stderr, shell-quoted and with secret values masked by
+/// [`Redactor::with_defaults`]. Bug reports that include the panic output then
+/// also say how to reproduce it.
+///
+/// Each call wraps whatever hook is installed at that point, so calling this
+/// more than once prints the command line more than once.
+///
+/// # Example
+///
+/// ```
+/// fn main() {
+///     argv::install_panic_hook();
+///
+///     // ...
+/// }
+/// ```
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        let command_line = Redactor::with_defaults().display(crate::iter());
+        // Writing to stderr may fail, and panicking within the hook would
+        // abort.
+        let _ = writeln!(io::stderr(), "command line: {}", command_line);
+    }));
+}
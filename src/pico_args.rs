@@ -0,0 +1,30 @@
+use std::ffi::OsString;
+
+/// Returns a [`pico_args::Arguments`] holding the command line.
+///
+/// Requires the `pico-args` feature. Equivalent to
+/// `pico_args::Arguments::from_env()`, except that the arguments come from
+/// [`argv::iter()`][crate::iter], so an override installed through
+/// [`argv::mock`][crate::mock] is seen by the parser too. As with `from_env`,
+/// the program name is not included.
+///
+/// pico-args takes ownership of a `Vec<OsString>`, which is allocated once at
+/// its final size.
+///
+/// [`pico_args::Arguments`]: https://docs.rs/pico-args/0.5/pico_args/struct.Arguments.html
+///
+/// # Example
+///
+/// ```
+/// let mut args = argv::pico_args_arguments();
+/// let verbose = args.contains(["-v", "--verbose"]);
+/// let rest = args.finish();
+/// # let _ = (verbose, rest);
+/// ```
+pub fn pico_args_arguments() -> ::pico_args::Arguments {
+    let mut iter = crate::iter();
+    let _program = iter.next();
+    let mut args = Vec::with_capacity(iter.len());
+    args.extend(iter.map(OsString::from));
+    ::pico_args::Arguments::from_vec(args)
+}
@@ -0,0 +1,428 @@
+use std::ffi::OsString;
+use std::io;
+use std::vec;
+
+/// Returns the command line arguments of another process.
+///
+/// On Linux this reads `/proc/<pid>/cmdline`. On macOS it parses the
+/// `KERN_PROCARGS2` sysctl, and on the BSDs it uses `KERN_PROC_ARGS`. Kernel
+/// threads have no command line and produce an empty vector.
+///
+/// Note that a process is free to rewrite its own arguments, for example to
+/// set its title, in which case this returns whatever the process has
+/// written: typically a single string with spaces in it. Trailing NUL padding
+/// left behind by such a rewrite is trimmed, which means trailing empty
+/// arguments are not reported.
+///
+/// # Errors
+///
+/// Fails if the process does not exist, if the caller is not permitted to
+/// inspect it, or if this is not supported on the current platform.
+pub fn of_pid(pid: u32) -> io::Result<Vec<OsString>> {
+    let cmdline = imp::cmdline(pid)?;
+    Ok(split(&cmdline))
+}
+
+/// Returns the command line arguments of the parent process.
+///
+/// This lets a program see how it was launched, for example to tell whether
+/// it is running under `make`, `cargo` or `ninja`. The same caveats as for
+/// [`of_pid`] apply.
+///
+/// # Errors
+///
+/// Fails if the parent process cannot be inspected, or if this is not
+/// supported on the current platform.
+pub fn parent() -> io::Result<Vec<OsString>> {
+    #[cfg(unix)]
+    return of_pid(std::os::unix::process::parent_id());
+
+    #[cfg(not(unix))]
+    return Err(io::Error::new(
+        io::ErrorKind::Other,
+        "reading the parent process's arguments is not supported on this platform",
+    ));
+}
+
+/// Returns an iterator over the command line arguments of every process
+/// visible to the caller.
+///
+/// The list of processes is taken up front. Processes that exit before they
+/// are reached, or that the caller is not permitted to inspect, are skipped.
+/// Kernel threads are included with an empty argument vector.
+///
+/// Supported on Linux, macOS and FreeBSD.
+///
+/// # Errors
+///
+/// Fails if the list of processes cannot be read, or if this is not supported
+/// on the current platform.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// # if cfg!(all(target_os = "linux", not(miri))) {
+/// for (pid, args) in argv::processes()? {
+///     println!("{:>7} {:?}", pid, args);
+/// }
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+pub fn processes() -> io::Result<Processes> {
+    let pids = imp::pids()?;
+    Ok(Processes {
+        pids: pids.into_iter(),
+    })
+}
+
+/// Iterator over the command lines of running processes.
+///
+/// Returned by [`processes`].
+#[derive(Debug)]
+pub struct Processes {
+    pids: vec::IntoIter<u32>,
+}
+
+impl Iterator for Processes {
+    type Item = (u32, Vec<OsString>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for pid in &mut self.pids {
+            if let Ok(args) = of_pid(pid) {
+                return Some((pid, args));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.pids.len()))
+    }
+}
+
+// The command line of a process as NUL-terminated arguments, possibly
+// followed by padding.
+pub(crate) fn cmdline(pid: u32) -> io::Result<Vec<u8>> {
+    imp::cmdline(pid)
+}
+
+// Splits a NUL-separated and possibly NUL-padded command line.
+#[cfg(unix)]
+fn split(mut bytes: &[u8]) -> Vec<OsString> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    while let [rest @ .., 0] = bytes {
+        bytes = rest;
+    }
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+    bytes
+        .split(|&b| b == 0)
+        .map(|arg| OsStr::from_bytes(arg).to_owned())
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn split(_bytes: &[u8]) -> Vec<OsString> {
+    Vec::new()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod imp {
+    use std::{fs, io};
+
+    pub(super) fn cmdline(pid: u32) -> io::Result<Vec<u8>> {
+        fs::read(format!("/proc/{}/cmdline", pid))
+    }
+
+    pub(super) fn pids() -> io::Result<Vec<u32>> {
+        let mut pids = Vec::new();
+        for entry in fs::read_dir("/proc")? {
+            let name = entry?.file_name();
+            if let Some(pid) = name.to_str().and_then(|name| name.parse().ok()) {
+                pids.push(pid);
+            }
+        }
+        pids.sort_unstable();
+        Ok(pids)
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod imp {
+    use super::sysctl;
+    use std::convert::TryInto;
+    use std::io;
+    use std::mem;
+    use std::os::raw::{c_int, c_void};
+    use std::ptr;
+
+    const CTL_KERN: c_int = 1;
+    const KERN_ARGMAX: c_int = 8;
+    const KERN_PROCARGS2: c_int = 49;
+
+    extern "C" {
+        fn proc_listallpids(buffer: *mut c_void, buffersize: c_int) -> c_int;
+    }
+
+    pub(super) fn cmdline(pid: u32) -> io::Result<Vec<u8>> {
+        let mut argmax: c_int = 0;
+        let mut len = mem::size_of::<c_int>();
+        let mib = [CTL_KERN, KERN_ARGMAX];
+        unsafe { sysctl::read(&mib, (&mut argmax as *mut c_int).cast(), &mut len)? };
+
+        let mib = [CTL_KERN, KERN_PROCARGS2, pid as c_int];
+        let mut buffer = vec![0u8; argmax as usize];
+        let mut len = buffer.len();
+        unsafe { sysctl::read(&mib, buffer.as_mut_ptr().cast(), &mut len)? };
+        buffer.truncate(len);
+        Ok(parse_procargs2(&buffer))
+    }
+
+    pub(super) fn pids() -> io::Result<Vec<u32>> {
+        loop {
+            let count = unsafe { proc_listallpids(ptr::null_mut(), 0) };
+            if count < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // Leave room for processes started in the meantime.
+            let capacity = count as usize + 64;
+            let mut pids = vec![0 as c_int; capacity];
+            let size = (capacity * mem::size_of::<c_int>()) as c_int;
+            let count = unsafe { proc_listallpids(pids.as_mut_ptr().cast(), size) };
+            if count < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if (count as usize) < capacity {
+                let mut pids: Vec<u32> = pids[..count as usize]
+                    .iter()
+                    .map(|&pid| pid as u32)
+                    .collect();
+                pids.sort_unstable();
+                return Ok(pids);
+            }
+        }
+    }
+
+    // The buffer holds argc as a native int, then the executable path, then
+    // NUL padding up to an aligned boundary, then argc NUL-terminated
+    // arguments, then the environment.
+    fn parse_procargs2(buffer: &[u8]) -> Vec<u8> {
+        let size = mem::size_of::<c_int>();
+        let argc = match buffer.get(..size) {
+            Some(argc) => c_int::from_ne_bytes(argc.try_into().unwrap()),
+            None => return Vec::new(),
+        };
+        let rest = &buffer[size..];
+        let exec_path_len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        let rest = &rest[exec_path_len..];
+        let padding = rest.iter().position(|&b| b != 0).unwrap_or(rest.len());
+        let rest = &rest[padding..];
+
+        let mut end = 0;
+        for _ in 0..argc {
+            match rest[end..].iter().position(|&b| b == 0) {
+                Some(nul) => end += nul + 1,
+                None => {
+                    end = rest.len();
+                    break;
+                }
+            }
+        }
+        rest[..end].to_vec()
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd"))]
+mod imp {
+    use super::sysctl;
+    use std::io;
+    use std::os::raw::c_int;
+
+    const CTL_KERN: c_int = 1;
+    #[cfg(not(target_os = "netbsd"))]
+    const KERN_PROC: c_int = 14;
+    #[cfg(not(target_os = "netbsd"))]
+    const KERN_PROC_ARGS: c_int = 7;
+    #[cfg(target_os = "netbsd")]
+    const KERN_PROC_ARGS: c_int = 48;
+    #[cfg(target_os = "netbsd")]
+    const KERN_PROC_ARGV: c_int = 1;
+
+    pub(super) fn cmdline(pid: u32) -> io::Result<Vec<u8>> {
+        #[cfg(not(target_os = "netbsd"))]
+        let mib = [CTL_KERN, KERN_PROC, KERN_PROC_ARGS, pid as c_int];
+        #[cfg(target_os = "netbsd")]
+        let mib = [CTL_KERN, KERN_PROC_ARGS, pid as c_int, KERN_PROC_ARGV];
+        sysctl::read_to_vec(&mib)
+    }
+
+    #[cfg(target_os = "freebsd")]
+    pub(super) fn pids() -> io::Result<Vec<u32>> {
+        use std::convert::TryInto;
+        use std::mem;
+        use std::os::raw::c_void;
+
+        const KERN_PROC_PROC: c_int = 8;
+
+        // Each record is a struct kinfo_proc, which starts with its own size
+        // and layout version followed by eight pointers and then the pid.
+        let pid_offset = 2 * mem::size_of::<c_int>() + 8 * mem::size_of::<*const c_void>();
+        let mib = [CTL_KERN, KERN_PROC, KERN_PROC_PROC, 0];
+        let buffer = sysctl::read_to_vec(&mib)?;
+        let mut pids = Vec::new();
+        let mut record = &buffer[..];
+        while record.len() >= pid_offset + mem::size_of::<c_int>() {
+            let field = |offset: usize| {
+                let bytes = &record[offset..offset + mem::size_of::<c_int>()];
+                c_int::from_ne_bytes(bytes.try_into().unwrap())
+            };
+            let size = field(0) as usize;
+            if size < pid_offset || size > record.len() {
+                break;
+            }
+            pids.push(field(pid_offset) as u32);
+            record = &record[size..];
+        }
+        pids.sort_unstable();
+        Ok(pids)
+    }
+
+    #[cfg(not(target_os = "freebsd"))]
+    pub(super) fn pids() -> io::Result<Vec<u32>> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "listing processes is not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(target_os = "openbsd")]
+mod imp {
+    use super::sysctl;
+    use std::ffi::CStr;
+    use std::io;
+    use std::mem;
+    use std::os::raw::{c_char, c_int};
+
+    const CTL_KERN: c_int = 1;
+    const KERN_PROC_ARGS: c_int = 55;
+    const KERN_PROC_ARGV: c_int = 1;
+
+    pub(super) fn cmdline(pid: u32) -> io::Result<Vec<u8>> {
+        let mib = [CTL_KERN, KERN_PROC_ARGS, pid as c_int, KERN_PROC_ARGV];
+        let buffer = sysctl::read_to_vec(&mib)?;
+
+        // The kernel fills the buffer with a NULL-terminated array of
+        // pointers to the arguments, which are stored later in the same
+        // buffer.
+        let mut cmdline = Vec::new();
+        let pointers = buffer.as_ptr().cast::<*const c_char>();
+        let count = buffer.len() / mem::size_of::<*const c_char>();
+        for i in 0..count {
+            let arg = unsafe { pointers.add(i).read_unaligned() };
+            if arg.is_null() {
+                break;
+            }
+            let arg = unsafe { CStr::from_ptr(arg) };
+            cmdline.extend_from_slice(arg.to_bytes_with_nul());
+        }
+        Ok(cmdline)
+    }
+
+    pub(super) fn pids() -> io::Result<Vec<u32>> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "listing processes is not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+mod imp {
+    use std::io;
+
+    pub(super) fn cmdline(_pid: u32) -> io::Result<Vec<u8>> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "reading another process's arguments is not supported on this platform",
+        ))
+    }
+
+    pub(super) fn pids() -> io::Result<Vec<u32>> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "listing processes is not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+mod sysctl {
+    use std::io;
+    use std::os::raw::{c_int, c_uint, c_void};
+    use std::ptr;
+
+    const ENOMEM: c_int = 12;
+
+    extern "C" {
+        fn sysctl(
+            name: *const c_int,
+            namelen: c_uint,
+            oldp: *mut c_void,
+            oldlenp: *mut usize,
+            newp: *const c_void,
+            newlen: usize,
+        ) -> c_int;
+    }
+
+    pub(super) unsafe fn read(mib: &[c_int], oldp: *mut c_void, len: &mut usize) -> io::Result<()> {
+        let namelen = mib.len() as c_uint;
+        if unsafe { sysctl(mib.as_ptr(), namelen, oldp, len, ptr::null(), 0) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    // Reads a variable-length value, growing the buffer until it fits. A
+    // result that fills the buffer exactly is treated as possibly truncated.
+    #[cfg_attr(any(target_os = "macos", target_os = "ios"), allow(dead_code))]
+    pub(super) fn read_to_vec(mib: &[c_int]) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0u8; 4096];
+        loop {
+            let mut len = buffer.len();
+            match unsafe { read(mib, buffer.as_mut_ptr().cast(), &mut len) } {
+                Ok(()) if len < buffer.len() => {
+                    buffer.truncate(len);
+                    return Ok(buffer);
+                }
+                Ok(()) => {}
+                Err(err) if err.raw_os_error() == Some(ENOMEM) => {}
+                Err(err) => return Err(err),
+            }
+            let new_len = 2 * buffer.len();
+            buffer.resize(new_len, 0);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+
+/// Compares the captured command line arguments against what the kernel
+/// reports in `/proc/self/cmdline`, returning the first difference.
+///
+/// The kernel reads `/proc/self/cmdline` from the memory it placed the
+/// arguments in at startup. A difference means either that something in the
+/// process has since written to that memory, such as a library implementing
+/// its own `setproctitle`, or that the arguments were captured from the
+/// wrong place, for example under an unusual dynamic loader. Services that
+/// depend on their arguments being exactly right can call this at startup
+/// as a sanity check.
+///
+/// Modifications made through this crate, such as [`title::set`] and
+/// [`scrub`], also show up as a difference, because they overwrite the
+/// original memory after moving the arguments elsewhere.
+///
+/// [`title::set`]: crate::title::set
+/// [`scrub`]: crate::scrub
+///
+/// # Errors
+///
+/// Fails if `/proc/self/cmdline` cannot be read, for example because procfs
+/// is not mounted.
+///
+/// # Example
+///
+/// ```
+/// # if cfg!(miri) { return; }
+/// if let Some(divergence) = argv::verify_against_procfs().unwrap() {
+///     eprintln!("warning: {}", divergence);
+/// }
+/// ```
+pub fn verify_against_procfs() -> io::Result<Option<Divergence>> {
+    let cmdline = fs::read("/proc/self/cmdline")?;
+    // Each argument is followed by a NUL terminator, except possibly the
+    // last one if it has been overwritten.
+    let terminated = match cmdline.split_last() {
+        Some((0, rest)) => rest,
+        _ => &cmdline,
+    };
+    let mut procfs = if cmdline.is_empty() {
+        None
+    } else {
+        Some(terminated.split(|&b| b == 0))
+    };
+    let mut captured = crate::iter();
+    let mut index = 0;
+    loop {
+        let captured = captured.next();
+        let procfs = procfs
+            .as_mut()
+            .and_then(Iterator::next)
+            .map(OsStr::from_bytes);
+        if captured.is_none() && procfs.is_none() {
+            return Ok(None);
+        }
+        if captured != procfs {
+            return Ok(Some(Divergence {
+                index,
+                captured: captured.map(OsStr::to_owned),
+                procfs: procfs.map(OsStr::to_owned),
+            }));
+        }
+        index += 1;
+    }
+}
+
+/// First difference between the captured arguments and `/proc/self/cmdline`,
+/// returned by [`verify_against_procfs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    index: usize,
+    captured: Option<OsString>,
+    procfs: Option<OsString>,
+}
+
+impl Divergence {
+    /// Position of the first argument that differs.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The captured argument at that position, or None if fewer arguments
+    /// were captured.
+    pub fn captured(&self) -> Option<&OsStr> {
+        self.captured.as_deref()
+    }
+
+    /// The argument at that position in `/proc/self/cmdline`, or None if it
+    /// has fewer arguments.
+    pub fn procfs(&self) -> Option<&OsStr> {
+        self.procfs.as_deref()
+    }
+}
+
+impl Display for Divergence {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "argument {} differs from /proc/self/cmdline: captured {:?}, procfs {:?}",
+            self.index, self.captured, self.procfs,
+        )
+    }
+}
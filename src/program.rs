@@ -0,0 +1,145 @@
+use crate::once::OnceCell;
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Returns the name of the program, normally `argv[0]`.
+///
+/// A program may be executed with an entirely empty argument vector, in which
+/// case [`iter`][crate::iter] produces nothing at all. Rather than leave every
+/// caller to handle that, this function synthesizes a name: on Linux from the
+/// path the kernel recorded as `AT_EXECFN`, otherwise from
+/// `std::env::current_exe()`, falling back to an empty string.
+pub fn program() -> &'static OsStr {
+    let _guard = crate::zero_alloc::enter();
+    match crate::iter().next() {
+        Some(arg0) => arg0,
+        None => synthesize(),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn synthesize() -> &'static OsStr {
+    use std::os::unix::ffi::OsStrExt;
+
+    match crate::auxv::execfn() {
+        Some(execfn) => OsStr::from_bytes(execfn.to_bytes()),
+        None => current_exe(),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn synthesize() -> &'static OsStr {
+    current_exe()
+}
+
+fn current_exe() -> &'static OsStr {
+    if crate::error::allocation().is_err() {
+        return OsStr::new("");
+    }
+    current_exe_static().map_or(OsStr::new(""), Path::as_os_str)
+}
+
+/// Returns the path of the current executable, as reported by
+/// `std::env::current_exe()`.
+///
+/// The path is resolved on the first call and leaked, so later calls return
+/// the same `&'static Path` without allocating. Returns None if the path could
+/// not be determined on the first call.
+pub fn current_exe_static() -> Option<&'static Path> {
+    static CURRENT_EXE: OnceCell<Option<&Path>> = OnceCell::new();
+
+    *CURRENT_EXE.get_or_init(|| {
+        env::current_exe()
+            .ok()
+            .map(|path| -> &Path { Box::leak(path.into_boxed_path()) })
+    })
+}
+
+/// Returns the path of the executable that `argv[0]` refers to, found the way
+/// a shell would have found it.
+///
+/// If [`program`] contains a path separator, it is taken as the path itself,
+/// which is relative to the working directory the program was started in if
+/// it is not absolute. Otherwise each directory in `PATH` is searched for an
+/// executable file by that name, as seen in the environment snapshot of
+/// [`vars`][crate::vars]; on Windows the extensions in `PATHEXT` are tried as
+/// well if the name has none. Unlike `current_exe_static`, this does not rely
+/// on `/proc` or any other facility of the operating system, but it only
+/// finds the binary if whoever executed the program followed the convention.
+///
+/// The path is resolved on the first call and leaked, so later calls return
+/// the same `&'static Path` without allocating. Returns None if the search
+/// found nothing.
+pub fn resolve_program_path() -> Option<&'static Path> {
+    static RESOLVED: OnceCell<Option<&Path>> = OnceCell::new();
+
+    *RESOLVED.get_or_init(|| crate::zero_alloc::exempt(|| resolve(program())))
+}
+
+fn resolve(program: &'static OsStr) -> Option<&'static Path> {
+    if program.is_empty() {
+        return None;
+    }
+    if has_separator(program) {
+        return Some(Path::new(program));
+    }
+    let path = crate::vars::var_os("PATH")?;
+    env::split_paths(path)
+        .flat_map(|dir| candidates(dir.join(program)))
+        .find(|candidate| is_executable(candidate))
+        .map(|candidate| -> &Path { Box::leak(candidate.into_boxed_path()) })
+}
+
+#[cfg(unix)]
+fn has_separator(program: &OsStr) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    program.as_bytes().contains(&b'/')
+}
+
+#[cfg(not(unix))]
+fn has_separator(program: &OsStr) -> bool {
+    program
+        .to_string_lossy()
+        .chars()
+        .any(std::path::is_separator)
+}
+
+#[cfg(not(windows))]
+fn candidates(path: PathBuf) -> Vec<PathBuf> {
+    vec![path]
+}
+
+#[cfg(windows)]
+fn candidates(path: PathBuf) -> Vec<PathBuf> {
+    if path.extension().is_some() {
+        return vec![path];
+    }
+    let pathext = crate::vars::var_os("PATHEXT")
+        .and_then(OsStr::to_str)
+        .unwrap_or(".COM;.EXE;.BAT;.CMD");
+    pathext
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| {
+            let mut candidate = path.clone().into_os_string();
+            candidate.push(ext);
+            PathBuf::from(candidate)
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata().map_or(false, |metadata| {
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    })
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
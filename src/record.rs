@@ -0,0 +1,126 @@
+use std::ffi::OsStr;
+#[cfg(feature = "mock")]
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Saves the command line to a file, byte for byte, so that it can later be
+/// replayed with [`replay_from`].
+///
+/// The file holds each argument followed by a NUL terminator. On Windows the
+/// arguments are stored as the UTF-16 code units that make up the `OsStr`,
+/// little endian, each terminated by a zero code unit, so that unpaired
+/// surrogates survive the round trip.
+///
+/// # Errors
+///
+/// Fails if the file cannot be written.
+pub fn record_to(path: impl AsRef<Path>) -> io::Result<()> {
+    let mut contents = Vec::new();
+    for arg in crate::iter() {
+        encode(arg, &mut contents);
+    }
+    fs::write(path, contents)
+}
+
+/// Installs a command line saved by [`record_to`] as the override returned by
+/// [`argv::iter()`][crate::iter] from now on.
+///
+/// Requires the `mock` feature. This is equivalent to calling
+/// [`argv::mock::set_override`][crate::mock::set_override] with the recorded
+/// arguments.
+///
+/// # Errors
+///
+/// Fails if the file cannot be read or was not written by `record_to`.
+#[cfg(feature = "mock")]
+pub fn replay_from(path: impl AsRef<Path>) -> io::Result<()> {
+    let contents = fs::read(path)?;
+    // An empty file is a command line with no arguments at all.
+    let args = if contents.is_empty() {
+        Vec::new()
+    } else {
+        decode(&contents)?
+    };
+    crate::mock::set_override(args);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn encode(arg: &OsStr, contents: &mut Vec<u8>) {
+    use std::os::unix::ffi::OsStrExt;
+
+    contents.extend_from_slice(arg.as_bytes());
+    contents.push(0);
+}
+
+#[cfg(windows)]
+fn encode(arg: &OsStr, contents: &mut Vec<u8>) {
+    use std::os::windows::ffi::OsStrExt;
+
+    for unit in arg.encode_wide().chain(Some(0)) {
+        contents.extend_from_slice(&unit.to_le_bytes());
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn encode(arg: &OsStr, contents: &mut Vec<u8>) {
+    contents.extend_from_slice(arg.to_string_lossy().as_bytes());
+    contents.push(0);
+}
+
+#[cfg(all(feature = "mock", unix))]
+fn decode(contents: &[u8]) -> io::Result<Vec<OsString>> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let contents = terminated(contents, 0)?;
+    Ok(contents
+        .split(|&byte| byte == 0)
+        .map(|arg| OsStr::from_bytes(arg).to_owned())
+        .collect())
+}
+
+#[cfg(all(feature = "mock", windows))]
+fn decode(contents: &[u8]) -> io::Result<Vec<OsString>> {
+    use std::os::windows::ffi::OsStringExt;
+
+    if contents.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    let units: Vec<u16> = contents
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect();
+    let units = terminated(&units, 0)?;
+    Ok(units
+        .split(|&unit| unit == 0)
+        .map(OsString::from_wide)
+        .collect())
+}
+
+#[cfg(all(feature = "mock", not(any(unix, windows))))]
+fn decode(contents: &[u8]) -> io::Result<Vec<OsString>> {
+    let contents = terminated(contents, 0)?;
+    Ok(contents
+        .split(|&byte| byte == 0)
+        .map(|arg| OsString::from(String::from_utf8_lossy(arg).into_owned()))
+        .collect())
+}
+
+// Strips the terminator of the last argument, checking that it is there.
+#[cfg(feature = "mock")]
+fn terminated<T: PartialEq>(contents: &[T], terminator: T) -> io::Result<&[T]> {
+    match contents.split_last() {
+        Some((last, rest)) if *last == terminator => Ok(rest),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(feature = "mock")]
+fn invalid() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "not a command line recorded by argv::record_to",
+    )
+}
@@ -0,0 +1,191 @@
+//! Rendering the command line for logs with secret values masked.
+//!
+//! # Example
+//!
+//! ```
+//! use argv::redact::Redactor;
+//! use std::ffi::OsStr;
+//!
+//! let mut redactor = Redactor::new();
+//! redactor.pattern("--password").pattern("AWS_*");
+//!
+//! let args = ["deploy", "--password", "hunter2", "AWS_SECRET_ACCESS_KEY=abc", "--verbose"];
+//! let args = args.iter().map(OsStr::new);
+//! assert_eq!(
+//!     redactor.display(args).to_string(),
+//!     "deploy --password '***' 'AWS_SECRET_ACCESS_KEY=***' --verbose",
+//! );
+//! ```
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::fmt::{self, Write};
+
+const MASK: &str = "***";
+
+/// Set of patterns identifying arguments whose values should be masked.
+///
+/// A pattern is matched against the name of an argument, which is the part
+/// before the first `=` if there is one, or the whole argument otherwise. A
+/// `*` in the pattern matches any sequence of characters.
+///
+/// - When `NAME=VALUE` matches, VALUE is masked. This covers both
+///   `--password=hunter2` and environment-style assignments like
+///   `AWS_SECRET_ACCESS_KEY=...`.
+///
+/// - When a flag without `=` matches, the argument following it is masked, as
+///   in `--password hunter2`.
+#[derive(Clone, Debug, Default)]
+pub struct Redactor {
+    patterns: Vec<String>,
+}
+
+impl Redactor {
+    /// Creates a redactor with no patterns.
+    pub fn new() -> Self {
+        Redactor {
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Creates a redactor with patterns for commonly used secret-bearing
+    /// flags and environment variables.
+    pub fn with_defaults() -> Self {
+        let mut redactor = Redactor::new();
+        for pattern in DEFAULT_PATTERNS {
+            redactor.pattern(pattern);
+        }
+        redactor
+    }
+
+    /// Adds a pattern.
+    pub fn pattern(&mut self, pattern: &str) -> &mut Self {
+        self.patterns.push(pattern.to_owned());
+        self
+    }
+
+    /// Whether an argument name matches any of the patterns.
+    pub fn matches(&self, name: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| glob(pattern.as_bytes(), name.as_bytes()))
+    }
+
+    /// Returns the arguments with secret values replaced by `***`.
+    pub fn redact<'a, I>(&self, args: I) -> Vec<Cow<'a, OsStr>>
+    where
+        I: IntoIterator<Item = &'a OsStr>,
+    {
+        let mut redacted = Vec::new();
+        let mut mask_next = false;
+        for arg in args {
+            if mask_next {
+                mask_next = false;
+                redacted.push(Cow::Borrowed(OsStr::new(MASK)));
+                continue;
+            }
+            let lossy = arg.to_string_lossy();
+            match lossy.find('=') {
+                Some(eq) if self.matches(&lossy[..eq]) => {
+                    let masked = format!("{}={}", &lossy[..eq], MASK);
+                    redacted.push(Cow::Owned(masked.into()));
+                    continue;
+                }
+                Some(_) => {}
+                None => mask_next = self.matches(&lossy),
+            }
+            redacted.push(Cow::Borrowed(arg));
+        }
+        redacted
+    }
+
+    /// Renders the arguments as a shell-quoted command line with secret values
+    /// masked.
+    pub fn display<'a, I>(&self, args: I) -> Display<'a>
+    where
+        I: IntoIterator<Item = &'a OsStr>,
+    {
+        Display {
+            args: self.redact(args),
+        }
+    }
+}
+
+// Flags and variables that conventionally carry credentials.
+const DEFAULT_PATTERNS: &[&str] = &[
+    "--password",
+    "--passwd",
+    "--pass",
+    "--token",
+    "--*-token",
+    "--secret",
+    "--*-secret",
+    "--api-key",
+    "--apikey",
+    "--access-key",
+    "--private-key",
+    "--auth",
+    "--authorization",
+    "*_PASSWORD",
+    "*_TOKEN",
+    "*_SECRET",
+    "*_SECRET_*",
+    "*_API_KEY",
+    "AWS_*",
+];
+
+/// Shell-quoted command line, returned by [`Redactor::display`].
+pub struct Display<'a> {
+    args: Vec<Cow<'a, OsStr>>,
+}
+
+impl fmt::Display for Display<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                formatter.write_char(' ')?;
+            }
+            write_quoted(formatter, &arg.to_string_lossy())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Display<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), formatter)
+    }
+}
+
+// Quotes an argument for a POSIX shell, leaving it bare if it consists only of
+// characters that have no special meaning.
+pub(crate) fn write_quoted(formatter: &mut dyn Write, arg: &str) -> fmt::Result {
+    if is_bare(arg) {
+        return formatter.write_str(arg);
+    }
+    formatter.write_char('\'')?;
+    for (i, part) in arg.split('\'').enumerate() {
+        if i > 0 {
+            formatter.write_str("'\\''")?;
+        }
+        formatter.write_str(part)?;
+    }
+    formatter.write_char('\'')
+}
+
+pub(crate) fn is_bare(arg: &str) -> bool {
+    let safe = |ch: char| ch.is_ascii_alphanumeric() || "@%+=:,./-_".contains(ch);
+    !arg.is_empty() && arg.chars().all(safe)
+}
+
+// Matches `text` against `pattern`, in which `*` matches any sequence.
+fn glob(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|i| glob(rest, &text[i..])),
+        Some((&ch, rest)) => match text.split_first() {
+            Some((&first, text)) => first == ch && glob(rest, text),
+            None => false,
+        },
+    }
+}
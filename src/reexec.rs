@@ -0,0 +1,210 @@
+use std::borrow::Cow;
+use std::ffi::{CStr, CString, OsStr};
+use std::io;
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+use std::ptr;
+
+extern "C" {
+    fn execv(path: *const c_char, argv: *const *const c_char) -> c_int;
+}
+
+/// Replaces the current process with a fresh execution of the current
+/// executable, passing the same arguments and environment.
+///
+/// The executable is the one located by
+/// [`current_exe_static`][crate::current_exe_static], and the arguments are
+/// the ones produced by [`iter_c_ptrs`][crate::iter_c_ptrs], including
+/// `argv[0]`. The environment is the current one, so variables set through
+/// `std::env::set_var` beforehand are seen by the new process. This is the
+/// building block for a daemon that upgrades itself in place by executing
+/// its newly installed binary, or that re-executes after entering a namespace
+/// or sandbox.
+///
+/// Like `std::os::unix::process::CommandExt::exec`, this only returns on
+/// failure. Buffered output, such as the contents of a `BufWriter` around
+/// stdout, is not flushed.
+///
+/// # Example
+///
+/// ```no_run
+/// let err = argv::reexec();
+/// eprintln!("failed to re-execute: {}", err);
+/// ```
+pub fn reexec() -> io::Error {
+    let path = match exe() {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    let mut argv: Vec<*const c_char> = crate::iter_c_ptrs().collect();
+    argv.push(ptr::null());
+    exec(&path, &argv)
+}
+
+/// Replaces the current process with a fresh execution of the program's
+/// binary as located through `/proc/self/exe`, passing the same arguments and
+/// environment.
+///
+/// This is for restarting into a new version after the binary on disk has
+/// been upgraded, which [`reexec`] does not handle reliably because the path
+/// it uses is resolved once and cached. If the binary was replaced by
+/// renaming a new file over it, the kernel reports the original path with a
+/// ` (deleted)` suffix; the new file at that path is executed if one exists.
+/// Otherwise, including when the binary was deleted without a replacement,
+/// `/proc/self/exe` itself is executed, which refers to the file the current
+/// process is running from.
+///
+/// Only returns on failure.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn reexec_proc_self_exe() -> io::Error {
+    let path = match proc_self_exe() {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    let mut argv: Vec<*const c_char> = crate::iter_c_ptrs().collect();
+    argv.push(ptr::null());
+    exec(&path, &argv)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn proc_self_exe() -> io::Result<CString> {
+    use std::fs;
+    use std::os::unix::ffi::OsStringExt;
+    use std::path::Path;
+
+    const PROC_SELF_EXE: &str = "/proc/self/exe";
+    const DELETED: &[u8] = b" (deleted)";
+
+    let target = fs::read_link(PROC_SELF_EXE)?.into_os_string().into_vec();
+    if let Some(original) = target
+        .len()
+        .checked_sub(DELETED.len())
+        .filter(|&len| target[len..] == *DELETED)
+        .map(|len| &target[..len])
+    {
+        if Path::new(OsStr::from_bytes(original)).is_file() {
+            if let Ok(path) = CString::new(original) {
+                return Ok(path);
+            }
+        }
+    }
+    Ok(CString::new(PROC_SELF_EXE).unwrap())
+}
+
+/// Begins re-executing the current program with a modified command line.
+///
+/// The returned builder starts out with the current arguments, including
+/// `argv[0]`, and [`Reexec::exec`] behaves like [`reexec`] with whatever
+/// arguments the builder holds by then. Arguments that are kept are passed on
+/// without being copied.
+///
+/// # Example
+///
+/// ```no_run
+/// let err = argv::reexec_with()
+///     .remove("--daemonize")
+///     .arg("--supervised")
+///     .exec();
+/// eprintln!("failed to re-execute: {}", err);
+/// ```
+pub fn reexec_with() -> Reexec {
+    Reexec {
+        args: crate::c_strs().map(Cow::Borrowed).collect(),
+        saw_nul: false,
+    }
+}
+
+/// Builder for re-executing the current program, returned by
+/// [`reexec_with`].
+pub struct Reexec {
+    args: Vec<Cow<'static, CStr>>,
+    saw_nul: bool,
+}
+
+impl Reexec {
+    /// Appends an argument.
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Self {
+        let arg = self.c_string(arg.as_ref());
+        self.args.push(Cow::Owned(arg));
+        self
+    }
+
+    /// Removes every argument equal to `arg`, other than `argv[0]`.
+    pub fn remove<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Self {
+        let arg = arg.as_ref().as_bytes();
+        let mut index = 0;
+        self.args.retain(|existing| {
+            index += 1;
+            index == 1 || existing.to_bytes() != arg
+        });
+        self
+    }
+
+    /// Replaces every argument equal to `from`, other than `argv[0]`, with
+    /// `to`.
+    pub fn replace<S: AsRef<OsStr>, T: AsRef<OsStr>>(&mut self, from: S, to: T) -> &mut Self {
+        let from = from.as_ref().as_bytes();
+        let to = self.c_string(to.as_ref());
+        for existing in self.args.iter_mut().skip(1) {
+            if existing.to_bytes() == from {
+                *existing = Cow::Owned(to.clone());
+            }
+        }
+        self
+    }
+
+    /// The arguments the program would be re-executed with.
+    pub fn get_args(&self) -> impl ExactSizeIterator<Item = &OsStr> {
+        self.args
+            .iter()
+            .map(|arg| OsStr::from_bytes(arg.to_bytes()))
+    }
+
+    /// Replaces the current process with a fresh execution of the current
+    /// executable, passing the arguments held by this builder and the current
+    /// environment.
+    ///
+    /// Only returns on failure, including if an argument given to the
+    /// builder contained a NUL byte.
+    pub fn exec(&mut self) -> io::Error {
+        if self.saw_nul {
+            return crate::error::new(io::ErrorKind::InvalidInput, "argument contains a NUL byte");
+        }
+        let path = match exe() {
+            Ok(path) => path,
+            Err(err) => return err,
+        };
+        let mut argv: Vec<*const c_char> = self.args.iter().map(|arg| arg.as_ptr()).collect();
+        argv.push(ptr::null());
+        exec(&path, &argv)
+    }
+
+    fn c_string(&mut self, arg: &OsStr) -> CString {
+        CString::new(arg.as_bytes()).unwrap_or_else(|_| {
+            self.saw_nul = true;
+            CString::default()
+        })
+    }
+}
+
+fn exe() -> io::Result<CString> {
+    let path = crate::current_exe_static().ok_or_else(|| {
+        crate::error::new(
+            io::ErrorKind::NotFound,
+            "current executable could not be located",
+        )
+    })?;
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+        crate::error::new(
+            io::ErrorKind::InvalidInput,
+            "path of current executable contains a NUL byte",
+        )
+    })
+}
+
+// The array must be terminated by a null pointer.
+fn exec(path: &CString, argv: &[*const c_char]) -> io::Error {
+    debug_assert!(argv.last().map_or(false, |ptr| ptr.is_null()));
+    unsafe { execv(path.as_ptr(), argv.as_ptr()) };
+    io::Error::last_os_error()
+}
@@ -0,0 +1,86 @@
+use crate::redact::Redactor;
+use std::fmt;
+
+/// Returns a description of how the program was run, for pasting into a bug
+/// report.
+///
+/// The report consists of the path of the executable, the shell-quoted
+/// command line with secret values masked by [`Redactor::with_defaults`],
+/// the target triple the program was compiled for, and the source the
+/// arguments were obtained from, one per line:
+///
+/// ```text
+/// program: /usr/local/bin/tool
+/// arguments: tool --token '***' input.txt
+/// platform: x86_64-unknown-linux-gnu
+/// capture source: Constructor
+/// ```
+///
+/// The capture source is one of the [`Source`][crate::source::Source]
+/// variants, or `mock`, `embed`, or `no_main` if the arguments were
+/// installed through [`argv::mock`][crate::mock], [`argv::embed`][crate::embed]
+/// or [`argv::no_main!`][crate::no_main], or `none` if no source produced any.
+///
+/// # Example
+///
+/// ```
+/// if argv::iter().any(|arg| arg == "--bug-report") {
+///     println!("{}", argv::report());
+///     # if false {
+///     std::process::exit(0);
+///     # }
+/// }
+/// ```
+pub fn report() -> Report {
+    Report { _private: () }
+}
+
+/// Bug report section describing the current invocation, returned by
+/// [`report`].
+pub struct Report {
+    _private: (),
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("program: ")?;
+        match crate::current_exe_static().or_else(crate::resolve_program_path) {
+            Some(path) => write!(formatter, "{}", path.display())?,
+            None => formatter.write_str("unknown")?,
+        }
+        let arguments = Redactor::with_defaults().display(crate::iter());
+        write!(formatter, "\narguments: {}", arguments)?;
+        write!(formatter, "\nplatform: {}", env!("ARGV_TARGET"))?;
+        formatter.write_str("\ncapture source: ")?;
+        write_source(formatter)
+    }
+}
+
+impl fmt::Debug for Report {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), formatter)
+    }
+}
+
+// Checked in the same order as by argv::iter().
+fn write_source(formatter: &mut fmt::Formatter) -> fmt::Result {
+    #[cfg(feature = "mock")]
+    {
+        if crate::mock::current().is_some() {
+            return formatter.write_str("mock");
+        }
+    }
+    if crate::embed::current().is_some() {
+        return formatter.write_str("embed");
+    }
+    #[cfg(unix)]
+    {
+        if crate::entry::current().is_some() {
+            return formatter.write_str("no_main");
+        }
+    }
+    match crate::source::used() {
+        Some(source) => write!(formatter, "{:?}", source),
+        None => formatter.write_str("none"),
+    }
+}
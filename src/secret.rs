@@ -0,0 +1,195 @@
+use crate::lock::lock;
+use std::alloc::{self, Layout};
+use std::fmt::{self, Debug};
+use std::io;
+#[cfg(unix)]
+use std::os::raw::{c_int, c_void};
+use std::sync::atomic::{self, Ordering};
+#[cfg(unix)]
+use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+use std::{ptr, slice};
+
+/// Overwrites the argument at `index` with asterisks in the memory read by
+/// `ps` and `/proc/<pid>/cmdline`.
+///
+/// This hides a secret passed on the command line, such as the value of
+/// `--password=...`, from other users on the system who can list processes.
+/// [`argv::iter()`][crate::iter] continues to produce the original value.
+///
+/// Like [`title::set`][crate::title::set], this must be called before the
+/// first call to `argv::iter()`. If the process title has been changed, the
+/// argument may already be gone from the displayed memory and this overwrites
+/// part of the title instead.
+///
+/// # Errors
+///
+/// Fails if `index` is out of range, if the original argument memory is not
+/// accessible on this platform, or if `argv::iter()` has already been called.
+pub fn scrub(index: usize) -> io::Result<()> {
+    let _guard = crate::zero_alloc::enter();
+    crate::r#impl::relocate()?;
+    let arg = crate::r#impl::original_arg(index)?;
+    let len = arg.end as usize - arg.start as usize;
+
+    let _guard = lock();
+    unsafe { ptr::write_bytes(arg.start, b'*', len) };
+    Ok(())
+}
+
+/// Removes the argument at `index` from every place it is stored and moves it
+/// into locked memory that is zeroed on drop.
+///
+/// The copy is placed in memory locked with `mlock(2)`, so that it is not
+/// written to swap. The argument's bytes are then zeroed both in the memory
+/// read by `ps` and `/proc/<pid>/cmdline`, and in this crate's own copy, after
+/// which [`argv::iter()`][crate::iter] produces an empty string in its place.
+///
+/// This must be called before the first call to `argv::iter()`.
+///
+/// # Errors
+///
+/// Fails if `index` is out of range, if the memory cannot be locked, if the
+/// original argument memory is not accessible on this platform, or if
+/// `argv::iter()` has already been called.
+pub fn take_secret(index: usize) -> io::Result<SecretArg> {
+    let _guard = crate::zero_alloc::enter();
+    crate::r#impl::relocate()?;
+    let arg = crate::r#impl::original_arg(index)?;
+
+    let _guard = lock();
+    let secret = crate::r#impl::take_from_copy(index, SecretArg::new)?;
+    let len = arg.end as usize - arg.start as usize;
+    unsafe { zeroize(arg.start, len) };
+
+    Ok(secret)
+}
+
+/// Secret command line argument, held in memory that is locked against
+/// swapping and zeroed on drop.
+///
+/// Obtained from [`take_secret`]. The `Debug` representation does not reveal
+/// the contents.
+pub struct SecretArg {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl SecretArg {
+    // Each secret gets whole pages of its own, because locks do not nest: the
+    // munlock on drop would otherwise unlock pages still holding another
+    // secret or unrelated data. The pages are locked before the secret is
+    // copied in, so that it is never in memory that could be swapped.
+    fn new(bytes: &[u8]) -> io::Result<Self> {
+        let len = bytes.len();
+        let layout = match layout(len) {
+            Some(layout) => layout,
+            None => {
+                return Ok(SecretArg {
+                    ptr: ptr::NonNull::dangling().as_ptr(),
+                    len,
+                })
+            }
+        };
+        let ptr = unsafe { alloc::alloc(layout) };
+        if ptr.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        if !unsafe { mlock(ptr, layout.size()) } {
+            let err = io::Error::last_os_error();
+            unsafe { alloc::dealloc(ptr, layout) };
+            return Err(err);
+        }
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, len) };
+        Ok(SecretArg { ptr, len })
+    }
+
+    /// The secret's bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// The secret as an `OsStr`.
+    #[cfg(unix)]
+    pub fn as_os_str(&self) -> &OsStr {
+        OsStr::from_bytes(self.as_bytes())
+    }
+}
+
+impl Drop for SecretArg {
+    fn drop(&mut self) {
+        if let Some(layout) = layout(self.len) {
+            unsafe {
+                zeroize(self.ptr, self.len);
+                munlock(self.ptr, layout.size());
+                alloc::dealloc(self.ptr, layout);
+            }
+        }
+    }
+}
+
+impl Debug for SecretArg {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("SecretArg(..)")
+    }
+}
+
+// Owns its pages exclusively.
+unsafe impl Send for SecretArg {}
+unsafe impl Sync for SecretArg {}
+
+// Whole pages holding `len` bytes, or None for an empty secret which needs no
+// memory.
+fn layout(len: usize) -> Option<Layout> {
+    if len == 0 {
+        return None;
+    }
+    let page_size = page_size();
+    let size = (len + page_size - 1) / page_size * page_size;
+    Some(Layout::from_size_align(size, page_size).unwrap())
+}
+
+// Volatile, so that the compiler cannot elide zeroing memory which is about to
+// be freed.
+unsafe fn zeroize(ptr: *mut u8, len: usize) {
+    for i in 0..len {
+        unsafe { ptr::write_volatile(ptr.add(i), 0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+unsafe fn mlock(ptr: *const u8, len: usize) -> bool {
+    extern "C" {
+        fn mlock(addr: *const c_void, len: usize) -> c_int;
+    }
+    unsafe { mlock(ptr.cast(), len) == 0 }
+}
+
+#[cfg(unix)]
+unsafe fn munlock(ptr: *const u8, len: usize) {
+    extern "C" {
+        fn munlock(addr: *const c_void, len: usize) -> c_int;
+    }
+    unsafe { munlock(ptr.cast(), len) };
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    extern "C" {
+        fn getpagesize() -> c_int;
+    }
+    unsafe { getpagesize() as usize }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> usize {
+    4096
+}
+
+#[cfg(not(unix))]
+unsafe fn mlock(_ptr: *const u8, _len: usize) -> bool {
+    true
+}
+
+#[cfg(not(unix))]
+unsafe fn munlock(_ptr: *const u8, _len: usize) {}
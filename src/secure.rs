@@ -0,0 +1,59 @@
+/// Returns whether the current process is running in a secure-execution
+/// context.
+///
+/// This is the case when the program was started through a setuid or setgid
+/// executable, or was granted file capabilities, or the kernel otherwise
+/// decided that the process should not trust its own environment. Programs in
+/// this situation should not act on command line arguments or environment
+/// variables that could have been chosen by a less privileged caller without
+/// validating them first.
+///
+/// On Linux this reads `AT_SECURE` from the auxiliary vector. On macOS and the
+/// BSDs it calls `issetugid(2)`. On other platforms it always returns false.
+pub fn is_secure_exec() -> bool {
+    let _guard = crate::zero_alloc::enter();
+    crate::secure::r#impl::is_secure_exec()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod r#impl {
+    pub(crate) fn is_secure_exec() -> bool {
+        crate::auxv::secure()
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+mod r#impl {
+    use std::os::raw::c_int;
+
+    extern "C" {
+        fn issetugid() -> c_int;
+    }
+
+    pub(crate) fn is_secure_exec() -> bool {
+        unsafe { issetugid() != 0 }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+mod r#impl {
+    pub(crate) fn is_secure_exec() -> bool {
+        false
+    }
+}
@@ -0,0 +1,200 @@
+use ::serde::ser::{Serialize, Serializer};
+use std::ffi::OsStr;
+
+/// Serialized as a sequence with one element per argument, so that an
+/// invocation can be embedded in structured logs or crash reports.
+///
+/// Requires the `serde` feature. An argument that is valid UTF-8 is serialized
+/// as a string. Any other argument is serialized losslessly: as bytes on Unix,
+/// and as a sequence of UTF-16 code units on Windows. Serializing does not
+/// consume the iterator.
+impl Serialize for crate::Iter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.clone().map(Arg))
+    }
+}
+
+pub(crate) struct Arg<'a>(pub &'a OsStr);
+
+impl Serialize for Arg<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if let Some(string) = self.0.to_str() {
+            return serializer.serialize_str(string);
+        }
+        serialize_non_utf8(self.0, serializer)
+    }
+}
+
+#[cfg(unix)]
+fn serialize_non_utf8<S>(arg: &OsStr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    use std::os::unix::ffi::OsStrExt;
+
+    serializer.serialize_bytes(arg.as_bytes())
+}
+
+#[cfg(windows)]
+fn serialize_non_utf8<S>(arg: &OsStr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    use std::os::windows::ffi::OsStrExt;
+
+    serializer.collect_seq(arg.encode_wide())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn serialize_non_utf8<S>(arg: &OsStr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&arg.to_string_lossy())
+}
+
+#[cfg(feature = "mock")]
+mod mock {
+    use super::Arg;
+    use crate::mock::Invocation;
+    use ::serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+    use ::serde::ser::{Serialize, Serializer};
+    use std::ffi::OsString;
+    use std::fmt;
+
+    impl Serialize for Invocation {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.collect_seq(self.args.iter().map(|arg| Arg(arg)))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Invocation {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct ArgsVisitor;
+
+            impl<'de> Visitor<'de> for ArgsVisitor {
+                type Value = Vec<OsString>;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a sequence of command line arguments")
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    let mut args = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                    while let Some(OwnedArg(arg)) = seq.next_element()? {
+                        args.push(arg);
+                    }
+                    Ok(args)
+                }
+            }
+
+            let args = deserializer.deserialize_seq(ArgsVisitor)?;
+            Ok(Invocation { args })
+        }
+    }
+
+    struct OwnedArg(OsString);
+
+    struct ArgVisitor;
+
+    impl<'de> Deserialize<'de> for OwnedArg {
+        #[cfg(unix)]
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            // Self-describing formats produce the string or bytes as written,
+            // and the others are able to hand over either one as bytes.
+            deserializer.deserialize_byte_buf(ArgVisitor)
+        }
+
+        #[cfg(not(unix))]
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(ArgVisitor)
+        }
+    }
+
+    impl<'de> Visitor<'de> for ArgVisitor {
+        type Value = OwnedArg;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a command line argument")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(OwnedArg(OsString::from(v)))
+        }
+
+        fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(OwnedArg(OsString::from(v)))
+        }
+
+        #[cfg(unix)]
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            self.visit_byte_buf(v.to_vec())
+        }
+
+        #[cfg(unix)]
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            use std::os::unix::ffi::OsStringExt;
+
+            Ok(OwnedArg(OsString::from_vec(v)))
+        }
+
+        #[cfg(unix)]
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(byte) = seq.next_element::<u8>()? {
+                bytes.push(byte);
+            }
+            self.visit_byte_buf(bytes)
+        }
+
+        #[cfg(windows)]
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            use std::os::windows::ffi::OsStringExt;
+
+            let mut units = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(unit) = seq.next_element::<u16>()? {
+                units.push(unit);
+            }
+            Ok(OwnedArg(OsString::from_wide(&units)))
+        }
+    }
+}
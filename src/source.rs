@@ -0,0 +1,374 @@
+//! Choosing where the command line is obtained from.
+//!
+//! On Linux with glibc the arguments are normally captured by a static
+//! constructor before main, or found in place on the initial stack if the
+//! crate is built without constructors. Elsewhere they come from
+//! `std::env::args_os`.
+//!
+//! Some environments cannot provide one or the other: a constructor may run
+//! without receiving arguments, a container or chroot may have no `/proc`,
+//! and a sandbox may forbid reading it. [`set_order`] controls which sources
+//! are consulted and in what order, so that behavior in such deployments is
+//! deterministic.
+//!
+//! ```
+//! use argv::source::Source;
+//!
+//! // Never touch /proc, even if nothing was captured before main.
+//! # let _ =
+//! argv::source::set_order(&[Source::Constructor, Source::Std]);
+//! ```
+//!
+//! Environments that none of the built-in sources understand, such as a
+//! simulator harness or an RTOS shim, can supply the command line themselves
+//! by implementing [`Capture`] and passing it to [`register`] at startup.
+//!
+//! ```
+//! use argv::source::Capture;
+//! use std::ffi::OsStr;
+//!
+//! struct Simulator;
+//!
+//! impl Capture for Simulator {
+//!     fn capture(&self) -> Vec<&'static OsStr> {
+//!         vec![OsStr::new("firmware"), OsStr::new("--board=sim")]
+//!     }
+//! }
+//!
+//! # let _ =
+//! argv::source::register(&Simulator);
+//! ```
+
+use crate::once::OnceCell;
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::io;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::thread;
+
+/// A place the command line arguments can be obtained from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Source {
+    /// The arguments received by this crate's static constructor before
+    /// main, on Linux with glibc. Unavailable elsewhere, or if the crate is
+    /// built with the `force-fallback` feature.
+    Constructor,
+    /// The arguments that the kernel placed on the initial stack, located
+    /// through glibc's `__libc_stack_end` and verified against
+    /// `program_invocation_name`. No constructor is involved and the strings
+    /// are not copied, which makes this the zero-copy alternative when the
+    /// crate is built with the `no-ctor` feature. Unavailable on platforms
+    /// other than Linux with glibc.
+    Libc,
+    /// The contents of `/proc/self/cmdline`. Unavailable on platforms
+    /// other than Linux, or if procfs is not mounted.
+    Procfs,
+    /// `std::env::args_os()`.
+    Std,
+    /// The source passed to [`register`]. Produces nothing if none was
+    /// registered.
+    Custom,
+}
+
+/// A source of command line arguments.
+///
+/// Every [`Source`] implements this trait, and other implementations can be
+/// installed with [`register`].
+pub trait Capture: Sync {
+    /// Obtains the command line, including the program name, or an empty
+    /// vector if this source has nothing to offer.
+    ///
+    /// Called once by this crate on first use of the arguments, and again on
+    /// every call to [`argv::iter_with(Source::Custom)`][crate::iter_with].
+    /// Strings that are not already `'static` can be leaked with
+    /// `Box::leak`.
+    fn capture(&self) -> Vec<&'static OsStr>;
+}
+
+impl Capture for Source {
+    fn capture(&self) -> Vec<&'static OsStr> {
+        if *self == crate::r#impl::SOURCE {
+            return if crate::r#impl::captured() {
+                crate::r#impl::iter().collect()
+            } else {
+                Vec::new()
+            };
+        }
+        match self {
+            Source::Constructor => Vec::new(),
+            Source::Libc => libc(),
+            Source::Procfs => procfs(),
+            Source::Std => std::env::args_os()
+                .map(|arg| -> &OsStr { Box::leak(arg.into_boxed_os_str()) })
+                .collect(),
+            Source::Custom => match custom() {
+                Some(custom) => custom.capture(),
+                None => Vec::new(),
+            },
+        }
+    }
+}
+
+/// The order used unless [`set_order`] is called.
+///
+/// [`Source::Libc`] is included only when the crate is built with the
+/// `no-ctor` feature on Linux with glibc, in place of the constructor.
+#[cfg(all(
+    target_os = "linux",
+    target_env = "gnu",
+    not(miri),
+    feature = "no-ctor",
+))]
+pub const DEFAULT_ORDER: &[Source] = &[Source::Custom, Source::Libc, Source::Std, Source::Procfs];
+/// The order used unless [`set_order`] is called.
+///
+/// [`Source::Libc`] is included only when the crate is built with the
+/// `no-ctor` feature on Linux with glibc, in place of the constructor.
+#[cfg(not(all(
+    target_os = "linux",
+    target_env = "gnu",
+    not(miri),
+    feature = "no-ctor",
+)))]
+pub const DEFAULT_ORDER: &[Source] = &[
+    Source::Custom,
+    Source::Constructor,
+    Source::Std,
+    Source::Procfs,
+];
+
+// ORDER is written only by the caller that moves STATE from DEFAULT to
+// SETTING, and is read once STATE is SET. USED means the default order was
+// consulted and can no longer change.
+const DEFAULT: usize = 0;
+const SETTING: usize = 1;
+const SET: usize = 2;
+const USED: usize = 3;
+static STATE: AtomicUsize = AtomicUsize::new(DEFAULT);
+static ORDER: AtomicPtr<&'static [Source]> = AtomicPtr::new(ptr::null_mut());
+// The arguments if obtained from a source other than the platform
+// implementation, and the source they were obtained from.
+static RESOLVED: OnceCell<(Option<&[&OsStr]>, Option<Source>)> = OnceCell::new();
+static CUSTOM: AtomicPtr<&'static dyn Capture> = AtomicPtr::new(ptr::null_mut());
+
+/// Sets the sources that the command line is obtained from, in order of
+/// preference.
+///
+/// The first source that produces a nonempty command line is used. Sources
+/// not in `order` are never consulted. If none produce anything,
+/// [`argv::iter()`][crate::iter] is empty.
+///
+/// # Errors
+///
+/// Fails if the order has already been set, or if the command line has
+/// already been obtained by an earlier call to `argv::iter()` or anything
+/// built on it.
+pub fn set_order(order: &'static [Source]) -> io::Result<()> {
+    if STATE
+        .compare_exchange(DEFAULT, SETTING, Ordering::Acquire, Ordering::Acquire)
+        .is_err()
+    {
+        return Err(crate::error::new(
+            io::ErrorKind::AlreadyExists,
+            "source order has already been set or used",
+        ));
+    }
+    ORDER.store(Box::into_raw(Box::new(order)), Ordering::Relaxed);
+    STATE.store(SET, Ordering::Release);
+    Ok(())
+}
+
+/// Installs a source of command line arguments, consulted wherever
+/// [`Source::Custom`] appears in the [order][order], which by default is
+/// first.
+///
+/// # Errors
+///
+/// Fails if a source has already been registered, or if the command line has
+/// already been obtained by an earlier call to
+/// [`argv::iter()`][crate::iter] or anything built on it.
+pub fn register(source: &'static dyn Capture) -> io::Result<()> {
+    if RESOLVED.get().is_some() {
+        return Err(crate::error::new(
+            io::ErrorKind::Other,
+            "command line has already been obtained",
+        ));
+    }
+    let source = Box::into_raw(Box::new(source));
+    if CUSTOM
+        .compare_exchange(ptr::null_mut(), source, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        drop(unsafe { Box::from_raw(source) });
+        return Err(crate::error::new(
+            io::ErrorKind::AlreadyExists,
+            "a custom source has already been registered",
+        ));
+    }
+    Ok(())
+}
+
+fn custom() -> Option<&'static dyn Capture> {
+    let custom = CUSTOM.load(Ordering::Acquire);
+    if custom.is_null() {
+        None
+    } else {
+        Some(unsafe { *custom })
+    }
+}
+
+/// Returns the order in which sources are consulted.
+pub fn order() -> &'static [Source] {
+    loop {
+        match STATE.load(Ordering::Acquire) {
+            SETTING => thread::yield_now(),
+            SET => return unsafe { *ORDER.load(Ordering::Relaxed) },
+            _ => return DEFAULT_ORDER,
+        }
+    }
+}
+
+/// Returns the source that the command line was obtained from, or None if
+/// no source produced any arguments.
+///
+/// This reveals when the usual source was unavailable and a later one was
+/// used instead. For example, in a shared library loaded with `dlopen` on a C
+/// library that passes no arguments to the constructors of shared objects,
+/// the arguments come from [`Source::Procfs`].
+///
+/// Arguments installed through [`argv::mock`][crate::mock] or
+/// [`argv::embed`][crate::embed] are not taken into account.
+pub fn used() -> Option<Source> {
+    resolved().1
+}
+
+// None if the platform implementation's arguments are the ones to use,
+// otherwise the arguments obtained from a different source.
+pub(crate) fn current() -> Option<&'static [&'static OsStr]> {
+    resolved().0
+}
+
+fn resolved() -> (Option<&'static [&'static OsStr]>, Option<Source>) {
+    *RESOLVED.get_or_init(resolve)
+}
+
+fn resolve() -> (Option<&'static [&'static OsStr]>, Option<Source>) {
+    let _ = STATE.compare_exchange(DEFAULT, USED, Ordering::Relaxed, Ordering::Relaxed);
+    for &source in order() {
+        if source == crate::r#impl::SOURCE {
+            if crate::r#impl::captured() {
+                return (None, Some(source));
+            }
+            continue;
+        }
+        let args = crate::zero_alloc::exempt(|| {
+            let args = source.capture();
+            &*Box::leak(args.into_boxed_slice())
+        });
+        if !args.is_empty() {
+            return (Some(args), Some(source));
+        }
+    }
+    (Some(&[]), None)
+}
+
+// Reads the source again instead of reusing what was obtained on first use,
+// without leaking anything.
+pub(crate) fn read_fresh(source: Source) -> Vec<Cow<'static, OsStr>> {
+    match source {
+        Source::Procfs => procfs_owned(),
+        Source::Std => std::env::args_os().map(Cow::Owned).collect(),
+        _ => source.capture().into_iter().map(Cow::Borrowed).collect(),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn procfs() -> Vec<&'static OsStr> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let cmdline: &'static [u8] = Box::leak(cmdline().into_boxed_slice());
+    split_cmdline(cmdline)
+        .into_iter()
+        .map(OsStr::from_bytes)
+        .collect()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn procfs_owned() -> Vec<Cow<'static, OsStr>> {
+    use std::os::unix::ffi::OsStrExt;
+
+    split_cmdline(&cmdline())
+        .into_iter()
+        .map(|arg| Cow::Owned(OsStr::from_bytes(arg).to_owned()))
+        .collect()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cmdline() -> Vec<u8> {
+    std::fs::read("/proc/self/cmdline").unwrap_or_default()
+}
+
+// Every argument is followed by a NUL, unless the process has overwritten
+// its arguments in a way that removed the last one.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn split_cmdline(cmdline: &[u8]) -> Vec<&[u8]> {
+    let cmdline = match cmdline.split_last() {
+        Some((0, terminated)) => terminated,
+        Some(_) => cmdline,
+        None => return Vec::new(),
+    };
+    cmdline.split(|&b| b == 0).collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn procfs() -> Vec<&'static OsStr> {
+    Vec::new()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn procfs_owned() -> Vec<Cow<'static, OsStr>> {
+    Vec::new()
+}
+
+// At the entry point, the stack pointer that glibc records in
+// __libc_stack_end points at argc, followed by the argv array. glibc also
+// sets program_invocation_name to argv[0], which confirms that nothing else
+// was found there.
+#[cfg(all(target_os = "linux", target_env = "gnu", not(miri)))]
+pub(crate) fn libc() -> Vec<&'static OsStr> {
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_long};
+    use std::os::unix::ffi::OsStrExt;
+
+    extern "C" {
+        static __libc_stack_end: *const c_long;
+        static program_invocation_name: *const c_char;
+    }
+
+    let (stack_end, name) = unsafe { (__libc_stack_end, program_invocation_name) };
+    if stack_end.is_null() || name.is_null() {
+        return Vec::new();
+    }
+    let argc = unsafe { *stack_end };
+    let argv = unsafe { stack_end.add(1) }.cast::<*const c_char>();
+    // program_invocation_name no longer points into the array once this crate
+    // has relocated the arguments, but then the constructor saw the array.
+    let (_, original_argv) = crate::r#impl::original_argv();
+    if argc <= 0 || unsafe { *argv } != name && argv != original_argv {
+        return Vec::new();
+    }
+    let argc = argc as usize;
+    if unsafe { !(*argv.add(argc)).is_null() } {
+        return Vec::new();
+    }
+    (0..argc)
+        .map(|i| OsStr::from_bytes(unsafe { CStr::from_ptr(*argv.add(i)) }.to_bytes()))
+        .collect()
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu", not(miri))))]
+pub(crate) fn libc() -> Vec<&'static OsStr> {
+    Vec::new()
+}
@@ -0,0 +1,207 @@
+use std::ops::Range;
+
+/// Returns the location of every command line argument within the raw
+/// command line the process was started with, or None if it is unavailable.
+///
+/// On Linux the raw command line is the block of memory in which the kernel
+/// placed the arguments, as returned by [`raw_region`][crate::raw_region],
+/// and offsets and lengths are in bytes. On Windows it is the string returned
+/// by `GetCommandLineW`, and offsets and lengths are in UTF-16 code units;
+/// each span covers the argument as it was typed, including any quotes and
+/// backslash escapes. Error messages can use the spans to point at the exact
+/// text the user entered, for example by underlining it.
+///
+/// Returns None on other platforms, if the arguments were not captured
+/// before main, or if they were installed through
+/// [`argv::mock`][crate::mock] or [`argv::embed`][crate::embed].
+///
+/// # Example
+///
+/// ```
+/// if let Some(spans) = argv::spans() {
+///     for (arg, span) in argv::iter().zip(spans) {
+///         eprintln!("{:?} at {}..{}", arg, span.offset(), span.offset() + span.len());
+///     }
+/// }
+/// ```
+pub fn spans() -> Option<Vec<Span>> {
+    if !crate::iter().is_native() {
+        return None;
+    }
+    r#impl::spans()
+}
+
+/// Location of one argument within the raw command line, returned by
+/// [`spans`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    offset: usize,
+    len: usize,
+}
+
+impl Span {
+    /// Position of the start of the argument.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Length of the argument, in the units described in [`spans`].
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the argument occupies nothing in the raw command line.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The range from [`offset`][Span::offset] to the end of the argument.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+#[cfg(unix)]
+mod r#impl {
+    use super::Span;
+
+    // Each argument is followed by a NUL and the next argument, so its extent
+    // follows from the pointers alone, even if the memory has since been
+    // overwritten by argv::title::set or a C library.
+    pub(super) fn spans() -> Option<Vec<Span>> {
+        let region = crate::r#impl::args_region()?;
+        let (argc, argv) = crate::r#impl::original_argv();
+        // No longer one span per argument after argv::refresh().
+        if argc != crate::iter().len() {
+            return None;
+        }
+        let start = region.start as usize;
+        let end = region.end as usize;
+        let mut spans = Vec::with_capacity(argc);
+        for i in 0..argc {
+            let arg = unsafe { *argv.add(i) } as usize;
+            let next = if i + 1 < argc {
+                unsafe { *argv.add(i + 1) as usize }
+            } else {
+                end
+            };
+            if arg < start || next <= arg || next > end {
+                return None;
+            }
+            spans.push(Span {
+                offset: arg - start,
+                len: next - arg - 1,
+            });
+        }
+        Some(spans)
+    }
+}
+
+#[cfg(windows)]
+pub(crate) use self::r#impl::{command_line, parse};
+
+#[cfg(windows)]
+mod r#impl {
+    use super::Span;
+
+    extern "system" {
+        fn GetCommandLineW() -> *const u16;
+    }
+
+    const SPACE: u16 = b' ' as u16;
+    const TAB: u16 = b'\t' as u16;
+    const QUOTE: u16 = b'"' as u16;
+    const BACKSLASH: u16 = b'\\' as u16;
+
+    pub(super) fn spans() -> Option<Vec<Span>> {
+        let spans = parse(command_line()?);
+        if spans.len() == crate::iter().len() {
+            Some(spans)
+        } else {
+            None
+        }
+    }
+
+    // The string is owned by the system and remains valid for the life of the
+    // process.
+    pub(crate) fn command_line() -> Option<&'static [u16]> {
+        let ptr = unsafe { GetCommandLineW() };
+        if ptr.is_null() {
+            return None;
+        }
+        let mut len = 0;
+        while unsafe { *ptr.add(len) } != 0 {
+            len += 1;
+        }
+        Some(unsafe { std::slice::from_raw_parts(ptr, len) })
+    }
+
+    // Finds the boundaries of the arguments the same way as the standard
+    // library splits them, following the rules of the Microsoft C runtime.
+    pub(crate) fn parse(cmdline: &[u16]) -> Vec<Span> {
+        let mut spans = Vec::new();
+        if cmdline.is_empty() {
+            return spans;
+        }
+
+        // The program name has no escapes and ends at whitespace outside
+        // quotes.
+        let mut i = 0;
+        let mut in_quotes = false;
+        while i < cmdline.len() {
+            match cmdline[i] {
+                QUOTE => in_quotes = !in_quotes,
+                SPACE | TAB if !in_quotes => break,
+                _ => {}
+            }
+            i += 1;
+        }
+        spans.push(Span { offset: 0, len: i });
+
+        loop {
+            while i < cmdline.len() && (cmdline[i] == SPACE || cmdline[i] == TAB) {
+                i += 1;
+            }
+            if i == cmdline.len() {
+                return spans;
+            }
+            let offset = i;
+            let mut in_quotes = false;
+            while i < cmdline.len() {
+                match cmdline[i] {
+                    SPACE | TAB if !in_quotes => break,
+                    BACKSLASH => {
+                        let mut count = 0;
+                        while i < cmdline.len() && cmdline[i] == BACKSLASH {
+                            count += 1;
+                            i += 1;
+                        }
+                        // An odd number of backslashes escapes a quote.
+                        if count % 2 == 1 && i < cmdline.len() && cmdline[i] == QUOTE {
+                            i += 1;
+                        }
+                        continue;
+                    }
+                    // Two quotes inside quotes are a literal quote.
+                    QUOTE if in_quotes && cmdline.get(i + 1) == Some(&QUOTE) => i += 1,
+                    QUOTE => in_quotes = !in_quotes,
+                    _ => {}
+                }
+                i += 1;
+            }
+            spans.push(Span {
+                offset,
+                len: i - offset,
+            });
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod r#impl {
+    use super::Span;
+
+    pub(super) fn spans() -> Option<Vec<Span>> {
+        None
+    }
+}
@@ -0,0 +1,59 @@
+/// Returns the combined length of all command line arguments, in bytes.
+///
+/// Lengths are as reported by `OsStr::len`, not counting NUL terminators.
+pub fn total_bytes() -> usize {
+    let _guard = crate::zero_alloc::enter();
+    crate::iter().map(|arg| arg.len()).sum()
+}
+
+/// Returns aggregate statistics about the command line arguments, computed in
+/// a single pass.
+///
+/// # Example
+///
+/// ```
+/// let stats = argv::stats();
+/// if stats.total_bytes() > 64 * 1024 {
+///     eprintln!("warning: consider passing arguments through a response file");
+/// }
+/// ```
+pub fn stats() -> Stats {
+    let _guard = crate::zero_alloc::enter();
+    let mut stats = Stats {
+        count: 0,
+        total_bytes: 0,
+        longest: 0,
+    };
+    for arg in crate::iter() {
+        stats.count += 1;
+        stats.total_bytes += arg.len();
+        stats.longest = stats.longest.max(arg.len());
+    }
+    stats
+}
+
+/// Aggregate statistics about the command line arguments, returned by
+/// [`stats`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stats {
+    count: usize,
+    total_bytes: usize,
+    longest: usize,
+}
+
+impl Stats {
+    /// Number of arguments, including `argv[0]`.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Combined length of the arguments in bytes, as by [`total_bytes`].
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Length in bytes of the longest argument, or 0 if there are none.
+    pub fn longest(&self) -> usize {
+        self.longest
+    }
+}
@@ -0,0 +1,84 @@
+//! Re-executing the current test binary with a chosen command line.
+//!
+//! Some behavior can only be observed through the arguments the operating
+//! system actually passed to a process, not through an override installed by
+//! [`argv::mock`][crate::mock]. These helpers let a single test file act as
+//! both the parent, which picks the arguments, and the child, which receives
+//! them.
+//!
+//! The libtest harness would interpret the chosen arguments as its own flags
+//! and filters, so the test target needs to provide its own `main`:
+//!
+//! ```toml
+//! [[test]]
+//! name = "cli"
+//! harness = false
+//! ```
+//!
+//! ```
+//! fn main() {
+//!     if argv::test::is_child() {
+//!         for arg in argv::iter() {
+//!             println!("{}", arg.to_string_lossy());
+//!         }
+//!         return;
+//!     }
+//!
+//!     # if cfg!(any(miri, not(unix))) { return; }
+//!     let output = argv::test::spawn_self(&["prog", "--flag", "two words"]);
+//!     assert!(output.status.success());
+//!     assert_eq!(output.stdout, b"prog\n--flag\ntwo words\n");
+//! }
+//! ```
+
+use std::env;
+use std::ffi::OsStr;
+use std::process::{Command, Output};
+
+const MARKER: &str = "ARGV_SPAWN_SELF";
+
+/// Runs the current executable again with `args` as its command line, and
+/// waits for it to finish, collecting its output.
+///
+/// The child has an environment variable set that makes [`is_child`] return
+/// true, and the `ARGV_OVERRIDE` variable removed so that it sees its real
+/// arguments. On Unix the first element of `args` becomes the child's
+/// `argv[0]`. Other platforms do not let the parent choose `argv[0]`
+/// independently of the program path, so the child receives the path of the
+/// executable in its place.
+///
+/// # Panics
+///
+/// Panics if the current executable cannot be located or spawned.
+pub fn spawn_self<I>(args: I) -> Output
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let exe = env::current_exe().expect("failed to locate current executable");
+    let mut args = args.into_iter();
+    let mut command = Command::new(exe);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        if let Some(arg0) = args.next() {
+            command.arg0(arg0);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = args.next();
+    }
+    command
+        .args(args)
+        .env(MARKER, "1")
+        .env_remove("ARGV_OVERRIDE")
+        .output()
+        .expect("failed to spawn current executable")
+}
+
+/// Returns whether this process was started by [`spawn_self`].
+pub fn is_child() -> bool {
+    env::var_os(MARKER).is_some()
+}
@@ -0,0 +1,435 @@
+//! Process title, as displayed by `ps` and in `/proc/<pid>/cmdline`.
+//!
+//! On Linux, tools like `ps` and `top` display a process by reading the memory
+//! in which the kernel originally placed its command line arguments. Changing
+//! the displayed title therefore means overwriting that memory, which this
+//! crate is in a position to do because it knows where the arguments live.
+//!
+//! Before the first overwrite, the arguments are moved to a private copy so
+//! that [`argv::iter()`][crate::iter] continues to produce the original
+//! command line. This is only possible as long as no reference into the
+//! original memory has been handed out, so set the title before the first
+//! call to `argv::iter()`. Note that `std::env::args_os()` reads the original
+//! memory directly and does observe the new title.
+//!
+//! The kernel places the environment strings directly after the arguments. To
+//! make room for titles longer than the original command line, the first call
+//! to [`set`] moves every environment variable to the heap through
+//! `std::env::set_var` and then reuses the memory the environment occupied.
+//! Pointers previously obtained from C `getenv` for the original environment
+//! do not survive this. Because modifying the environment races with other
+//! threads reading it, including through `std::env::var` and C libraries that
+//! call `getenv`, the first call to `set` must happen while the program is
+//! still single-threaded, typically at the start of main.
+//!
+//! On Linux, a title that does not fit even then is placed in a heap buffer
+//! that the kernel is told to display instead, using
+//! `prctl(PR_SET_MM_ARG_START/ARG_END)`. This requires `CAP_SYS_RESOURCE`;
+//! without it the title is truncated to fit in the original memory.
+//!
+//! On the BSDs, which display titles through a dedicated kernel mechanism, the
+//! title is set by the C library's `setproctitle(3)` instead. OpenBSD and
+//! NetBSD prefix the title with the program name. The `setproctitle` of
+//! libbsd on Linux is not used: it overwrites the same memory as this crate,
+//! and would add a dependency on a library that is not always installed.
+
+use crate::lock::lock;
+use crate::once::OnceCell;
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{ptr, slice};
+
+/// Sets the process title.
+///
+/// The title is truncated if it neither fits in the space originally occupied
+/// by the command line arguments and environment, nor can be relocated to the
+/// heap by `prctl`.
+///
+/// The first call moves the environment as described in the [module
+/// documentation][self], and must not run concurrently with any other thread
+/// reading or modifying the environment. Later calls do not touch the
+/// environment.
+///
+/// # Errors
+///
+/// Fails if the process title cannot be changed on this platform, if
+/// [`argv::iter()`][crate::iter] has already been called, or if the
+/// `zero-alloc` feature is enabled and the arguments would need to be moved.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// # if cfg!(all(
+/// #     target_os = "linux",
+/// #     not(target_env = "musl"),
+/// #     not(feature = "zero-alloc"),
+/// #     not(feature = "force-fallback"),
+/// # )) {
+/// argv::title::set("myd: worker 3 idle")?;
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+pub fn set(title: impl AsRef<OsStr>) -> io::Result<()> {
+    let _guard = crate::zero_alloc::enter();
+    let title = title.as_ref();
+    if let Some(result) = native::set(title) {
+        return result;
+    }
+
+    crate::r#impl::relocate()?;
+    let region = title_region().ok_or_else(unsupported)?;
+    let title = to_bytes(title);
+    let capacity = region.end as usize - region.start as usize;
+
+    let _guard = lock();
+
+    let heap = heap();
+    if (heap.is_some() || title.len() >= capacity) && unsafe { set_on_heap(&title, &region) } {
+        return Ok(());
+    }
+
+    let (start, capacity) = heap.unwrap_or((region.start as usize, capacity));
+    // Always leave room for a trailing NUL.
+    let len = title.len().min(capacity - 1);
+    unsafe { write(start as *mut u8, capacity, &title[..len]) };
+    Ok(())
+}
+
+/// Restores the original command line as the process title.
+///
+/// Does nothing if the title has never been set.
+///
+/// # Errors
+///
+/// Fails if the process title cannot be changed on this platform.
+pub fn restore() -> io::Result<()> {
+    let _guard = crate::zero_alloc::enter();
+    if let Some(result) = native::restore() {
+        return result;
+    }
+
+    let (region, original) = match TITLE_REGION.get() {
+        Some(Some((start, end, original))) => (*start as *mut u8..*end as *mut u8, *original),
+        Some(None) => return Err(unsupported()),
+        None => return Ok(()),
+    };
+    let capacity = region.end as usize - region.start as usize;
+
+    let _guard = lock();
+    if let Some((start, heap_capacity)) = heap() {
+        let heap = start..start + heap_capacity;
+        let region = region.start as usize..region.end as usize;
+        if unsafe { set_arg_region(heap, region) } {
+            set_heap(None);
+        } else {
+            unsafe { write(start as *mut u8, heap_capacity, original) };
+            return Ok(());
+        }
+    }
+    unsafe { write(region.start, capacity, original) };
+    Ok(())
+}
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+mod native {
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::os::raw::c_char;
+    use std::os::unix::ffi::OsStrExt;
+    use std::ptr;
+
+    extern "C" {
+        fn setproctitle(fmt: *const c_char, ...);
+    }
+
+    // A leading '-' tells FreeBSD not to prefix the title with the program
+    // name.
+    #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+    const FORMAT: &[u8] = b"-%s\0";
+    #[cfg(any(target_os = "netbsd", target_os = "openbsd"))]
+    const FORMAT: &[u8] = b"%s\0";
+
+    pub(super) fn set(title: &OsStr) -> Option<io::Result<()>> {
+        if let Err(err) = crate::error::allocation() {
+            return Some(Err(err));
+        }
+        let title = match CString::new(title.as_bytes()) {
+            Ok(title) => title,
+            Err(err) => return Some(Err(io::Error::new(io::ErrorKind::InvalidInput, err))),
+        };
+        let format = FORMAT.as_ptr().cast::<c_char>();
+        unsafe { setproctitle(format, title.as_ptr()) };
+        Some(Ok(()))
+    }
+
+    pub(super) fn restore() -> Option<io::Result<()>> {
+        unsafe { setproctitle(ptr::null()) };
+        Some(Ok(()))
+    }
+}
+
+#[cfg(not(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+mod native {
+    use std::ffi::OsStr;
+    use std::io;
+
+    pub(super) fn set(_title: &OsStr) -> Option<io::Result<()>> {
+        None
+    }
+
+    pub(super) fn restore() -> Option<io::Result<()>> {
+        None
+    }
+}
+
+// Start and capacity of the heap buffer the kernel has been told to display
+// as the process title, if any; a capacity of zero means none. Protected by
+// the lock.
+static HEAP_START: AtomicUsize = AtomicUsize::new(0);
+static HEAP_CAPACITY: AtomicUsize = AtomicUsize::new(0);
+
+// Must hold the lock.
+fn heap() -> Option<(usize, usize)> {
+    match HEAP_CAPACITY.load(Ordering::Relaxed) {
+        0 => None,
+        capacity => Some((HEAP_START.load(Ordering::Relaxed), capacity)),
+    }
+}
+
+// Must hold the lock.
+fn set_heap(heap: Option<(usize, usize)>) {
+    let (start, capacity) = heap.unwrap_or((0, 0));
+    HEAP_START.store(start, Ordering::Relaxed);
+    HEAP_CAPACITY.store(capacity, Ordering::Relaxed);
+}
+
+// Displays `title` from a heap buffer, growing it if necessary. Returns false
+// if the kernel refused to move the displayed region. Must hold the lock.
+unsafe fn set_on_heap(title: &[u8], region: &Range<*mut u8>) -> bool {
+    let current = match heap() {
+        Some((start, capacity)) if title.len() < capacity => {
+            unsafe { write(start as *mut u8, capacity, title) };
+            return true;
+        }
+        Some((start, capacity)) => start..start + capacity,
+        None => region.start as usize..region.end as usize,
+    };
+
+    let capacity = (title.len() + 1).max(2 * (current.end - current.start));
+    let buffer = Box::leak(vec![0u8; capacity].into_boxed_slice());
+    buffer[..title.len()].copy_from_slice(title);
+    let start = buffer.as_mut_ptr() as usize;
+    if unsafe { set_arg_region(current, start..start + capacity) } {
+        // The previous heap buffer, if any, is leaked because the kernel might
+        // be reading from it concurrently.
+        set_heap(Some((start, capacity)));
+        true
+    } else {
+        drop(unsafe { Box::from_raw(buffer) });
+        false
+    }
+}
+
+// Tells the kernel to display the process title from `new` instead of
+// `current`.
+#[cfg(target_os = "linux")]
+unsafe fn set_arg_region(current: Range<usize>, new: Range<usize>) -> bool {
+    use std::os::raw::{c_int, c_ulong};
+
+    const PR_SET_MM: c_int = 35;
+    const PR_SET_MM_ARG_START: c_ulong = 8;
+    const PR_SET_MM_ARG_END: c_ulong = 9;
+
+    extern "C" {
+        fn prctl(option: c_int, ...) -> c_int;
+    }
+
+    let set = |field: c_ulong, addr: usize| unsafe {
+        prctl(
+            PR_SET_MM,
+            field,
+            addr as c_ulong,
+            0 as c_ulong,
+            0 as c_ulong,
+        ) == 0
+    };
+
+    // The kernel rejects any intermediate state in which the start would be
+    // past the end.
+    let start = (PR_SET_MM_ARG_START, new.start);
+    let end = (PR_SET_MM_ARG_END, new.end);
+    let (first, second) = if new.start < current.end {
+        (start, end)
+    } else {
+        (end, start)
+    };
+    set(first.0, first.1) && set(second.0, second.1)
+}
+
+#[cfg(not(target_os = "linux"))]
+unsafe fn set_arg_region(_current: Range<usize>, _new: Range<usize>) -> bool {
+    false
+}
+
+// Writes `bytes` at the start of the title region and zeroes the remainder.
+unsafe fn write(start: *mut u8, capacity: usize, bytes: &[u8]) {
+    unsafe {
+        ptr::copy_nonoverlapping(bytes.as_ptr(), start, bytes.len());
+        ptr::write_bytes(start.add(bytes.len()), 0, capacity - bytes.len());
+    }
+}
+
+/// Returns the process title as currently displayed by `ps`.
+///
+/// Arguments are joined by a space, the way `ps` renders them. If nothing has
+/// set a title, this is the original command line.
+///
+/// # Errors
+///
+/// Fails if the process title cannot be read on this platform.
+pub fn get() -> io::Result<OsString> {
+    let region = if TITLE_REGION.get().is_some() {
+        title_region()
+    } else {
+        crate::r#impl::args_region()
+    };
+    let bytes = match region {
+        Some(region) => {
+            let _guard = lock();
+            let (start, len) = heap().unwrap_or((
+                region.start as usize,
+                region.end as usize - region.start as usize,
+            ));
+            unsafe { slice::from_raw_parts(start as *const u8, len) }.to_vec()
+        }
+        None => crate::process::cmdline(std::process::id())?,
+    };
+    Ok(from_bytes(render(bytes)))
+}
+
+// Start and end of the title region, and a copy of the original arguments
+// that occupied it.
+static TITLE_REGION: OnceCell<Option<(usize, usize, &[u8])>> = OnceCell::new();
+
+// The memory available for the title: the original arguments, followed by as
+// much of the original environment as could be moved out of the way.
+fn title_region() -> Option<Range<*mut u8>> {
+    let (start, end, _original) = TITLE_REGION.get_or_init(compute_title_region).as_ref()?;
+    Some(*start as *mut u8..*end as *mut u8)
+}
+
+fn compute_title_region() -> Option<(usize, usize, &'static [u8])> {
+    let args = crate::r#impl::args_region()?;
+    let stack = crate::r#impl::stack_region()?;
+    let start = args.start as usize;
+    let mut end = args.end as usize;
+    let original = unsafe { slice::from_raw_parts(args.start, end - start) };
+    let original: &[u8] = Box::leak(Box::from(original));
+    if stack.end > args.end {
+        relocate_environment();
+        let env = args.end as usize..stack.end as usize;
+        end = lowest_environment_pointer(env.clone()).unwrap_or(env.end);
+    }
+    Some((start, end, original))
+}
+
+// Setting a variable makes libc store a freshly allocated copy of it, so
+// afterward no entry of `environ` points into the kernel's original memory.
+// Only sound while no other thread uses the environment, which the
+// documentation of set() requires of its first call.
+fn relocate_environment() {
+    for (key, value) in std::env::vars_os() {
+        let valid = !key.is_empty() && !to_bytes(&key).contains(&b'=');
+        if valid {
+            std::env::set_var(key, value);
+        }
+    }
+}
+
+// Any variable that could not be moved limits how much of the original
+// environment memory can be reused.
+#[cfg(unix)]
+fn lowest_environment_pointer(range: Range<usize>) -> Option<usize> {
+    use std::os::raw::c_char;
+
+    extern "C" {
+        static environ: *const *const c_char;
+    }
+
+    let mut lowest = None;
+    let mut env = unsafe { environ };
+    if env.is_null() {
+        return None;
+    }
+    loop {
+        let ptr = unsafe { *env } as usize;
+        if ptr == 0 {
+            return lowest;
+        }
+        if range.contains(&ptr) && lowest.map_or(true, |lowest| ptr < lowest) {
+            lowest = Some(ptr);
+        }
+        env = unsafe { env.add(1) };
+    }
+}
+
+#[cfg(not(unix))]
+fn lowest_environment_pointer(_range: Range<usize>) -> Option<usize> {
+    None
+}
+
+fn render(mut bytes: Vec<u8>) -> Vec<u8> {
+    while bytes.last() == Some(&b'\0') {
+        bytes.pop();
+    }
+    for byte in &mut bytes {
+        if *byte == b'\0' {
+            *byte = b' ';
+        }
+    }
+    bytes
+}
+
+#[cfg(unix)]
+fn from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+#[cfg(not(unix))]
+fn from_bytes(bytes: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(unix)]
+fn to_bytes(title: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(title.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn to_bytes(title: &OsStr) -> Cow<'_, [u8]> {
+    Cow::Owned(title.to_string_lossy().into_owned().into_bytes())
+}
+
+fn unsupported() -> io::Error {
+    crate::error::new(
+        io::ErrorKind::Other,
+        "the process title is not supported on this platform",
+    )
+}
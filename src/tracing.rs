@@ -0,0 +1,74 @@
+//! Attaching the command line to [tracing] spans.
+//!
+//! Requires the `tracing` feature. tracing only records values for fields
+//! that were declared when the span was created, so declare [`PROGRAM`] and
+//! [`ARGS`] as empty and fill them in with [`record`]:
+//!
+//! ```text
+//! use argv::redact::Redactor;
+//!
+//! let span = tracing::info_span!(
+//!     "main",
+//!     argv.program = tracing::field::Empty,
+//!     argv.args = tracing::field::Empty,
+//! );
+//! argv::tracing::record(&span, &Redactor::with_defaults());
+//! ```
+//!
+//! Alternatively [`fields`] returns the values for passing directly to the
+//! span macro.
+//!
+//! [tracing]: https://docs.rs/tracing
+
+use crate::redact::Redactor;
+use ::tracing::Span;
+use std::path::Path;
+
+/// Name of the field holding the file name of the program.
+pub const PROGRAM: &str = "argv.program";
+
+/// Name of the field holding the shell-quoted command line.
+pub const ARGS: &str = "argv.args";
+
+/// Values of the [`PROGRAM`] and [`ARGS`] fields, returned by [`fields`].
+#[derive(Clone, Debug)]
+pub struct Fields {
+    program: String,
+    args: String,
+}
+
+impl Fields {
+    /// The file name of [`argv::program()`][crate::program].
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// The shell-quoted command line, with secret values masked by the
+    /// redactor passed to [`fields`].
+    pub fn args(&self) -> &str {
+        &self.args
+    }
+}
+
+/// Computes the values of the [`PROGRAM`] and [`ARGS`] fields for the current
+/// process.
+///
+/// Pass `&Redactor::new()` to include every argument as it is.
+pub fn fields(redactor: &Redactor) -> Fields {
+    let program = crate::program();
+    let program = Path::new(program).file_name().unwrap_or(program);
+    Fields {
+        program: program.to_string_lossy().into_owned(),
+        args: redactor.display(crate::iter()).to_string(),
+    }
+}
+
+/// Records the [`PROGRAM`] and [`ARGS`] fields on `span`.
+///
+/// Fields that were not declared when the span was created are ignored by
+/// tracing.
+pub fn record(span: &Span, redactor: &Redactor) {
+    let fields = fields(redactor);
+    span.record(PROGRAM, fields.program());
+    span.record(ARGS, fields.args());
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::ops::Range;
+
+/// Renders the command line with a marker beneath one argument, for error
+/// messages that point at what the user typed.
+///
+/// The command line is shell-quoted the same way as by
+/// [`Redactor::display`][crate::redact::Redactor::display], without masking
+/// anything. Returns None if there is no argument at `index`.
+///
+/// # Example
+///
+/// ```
+/// if let Some(index) = argv::position_of("--frobnicate") {
+///     eprintln!("error: unknown flag");
+///     eprintln!("{}", argv::underline(index).unwrap());
+/// }
+/// ```
+///
+/// ```text
+/// error: unknown flag
+/// tool --frobnicate 'two words'
+///      ^^^^^^^^^^^^
+/// ```
+pub fn underline(index: usize) -> Option<Underline> {
+    render(index, None)
+}
+
+/// Renders the command line with a marker beneath part of one argument.
+///
+/// The range is in bytes of the argument as converted by
+/// `OsStr::to_string_lossy`, which for an argument that is valid UTF-8 is
+/// the argument itself. An empty range is marked with a single caret at its
+/// position. Returns None if there is no argument at `index`, or if `range`
+/// is out of bounds or does not fall on character boundaries.
+///
+/// # Example
+///
+/// ```
+/// for (index, arg) in argv::iter().enumerate() {
+///     if let Some(value) = arg.to_str().and_then(|arg| arg.strip_prefix("--jobs=")) {
+///         if value.parse::<usize>().is_err() {
+///             let start = "--jobs=".len();
+///             let marker = argv::underline_range(index, start..start + value.len());
+///             eprintln!("error: invalid number of jobs\n{}", marker.unwrap());
+///         }
+///     }
+/// }
+/// ```
+pub fn underline_range(index: usize, range: Range<usize>) -> Option<Underline> {
+    render(index, Some(range))
+}
+
+// Marks the whole argument including its quotes if `range` is None.
+fn render(index: usize, range: Option<Range<usize>>) -> Option<Underline> {
+    let mut line = String::new();
+    let mut columns = None;
+    let mut column = 0;
+    for (i, arg) in crate::iter().enumerate() {
+        if i > 0 {
+            line.push(' ');
+            column += 1;
+        }
+        let arg = arg.to_string_lossy();
+        let start = line.len();
+        crate::redact::write_quoted(&mut line, &arg).unwrap();
+        let width = line[start..].chars().count();
+        if i == index {
+            columns = Some(match &range {
+                None => column..column + width,
+                Some(range) => {
+                    let prefix = arg.get(..range.start)?;
+                    let marked = arg.get(range.clone())?;
+                    let quote = usize::from(!crate::redact::is_bare(&arg));
+                    let start = column + quote + quoted_width(prefix);
+                    start..start + quoted_width(marked)
+                }
+            });
+        }
+        column += width;
+    }
+    Some(Underline {
+        line,
+        columns: columns?,
+    })
+}
+
+// Width of text inside single quotes, where each ' becomes '\''.
+fn quoted_width(text: &str) -> usize {
+    text.chars().map(|ch| if ch == '\'' { 4 } else { 1 }).sum()
+}
+
+/// Command line with a marker beneath part of it, returned by [`underline`]
+/// and [`underline_range`].
+///
+/// Displays as two lines: the command line, then carets beneath the marked
+/// part, without a trailing newline. Columns count characters, which lines
+/// up in a terminal as long as every character is one column wide.
+#[derive(Clone, Debug)]
+pub struct Underline {
+    line: String,
+    columns: Range<usize>,
+}
+
+impl Underline {
+    /// The shell-quoted command line.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// The marked columns of [`line`][Underline::line], counted in
+    /// characters.
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+}
+
+impl fmt::Display for Underline {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let width = (self.columns.end - self.columns.start).max(1);
+        write!(
+            formatter,
+            "{}\n{:indent$}{}",
+            self.line,
+            "",
+            "^".repeat(width),
+            indent = self.columns.start,
+        )
+    }
+}
@@ -0,0 +1,53 @@
+// Remembers which of the process's own arguments are valid UTF-8, so that
+// code walking the arguments as strings more than once validates each of them
+// only the first time.
+//
+// Each slot records the address of a string found to be valid. The strings
+// that native arguments point to are never modified, except for
+// take_secret() emptying one, which leaves a valid string too. So a match on
+// the address is sufficient, even after argv::refresh() has moved arguments
+// to other positions and whichever thread is iterating over what. The
+// position only picks the slot. Invalid arguments are not remembered and
+// neither are arguments installed by argv::mock or argv::embed, which are
+// validated every time.
+
+use std::ffi::OsStr;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Slots for the first 256 arguments; later ones are not cached.
+#[cfg(unix)]
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: AtomicUsize = AtomicUsize::new(0);
+#[cfg(unix)]
+static VALID: [AtomicUsize; 256] = [EMPTY; 256];
+
+// Equivalent to arg.to_str() for the native argument at `index`.
+#[cfg(unix)]
+pub(crate) fn native_to_str(index: usize, arg: &'static OsStr) -> Option<&'static str> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::str;
+
+    let slot = match VALID.get(index) {
+        Some(slot) => slot,
+        None => return arg.to_str(),
+    };
+
+    let bytes = arg.as_bytes();
+    if slot.load(Ordering::Acquire) == bytes.as_ptr() as usize {
+        return Some(unsafe { str::from_utf8_unchecked(bytes) });
+    }
+
+    let string = arg.to_str();
+    if string.is_some() {
+        slot.store(bytes.as_ptr() as usize, Ordering::Release);
+    }
+    string
+}
+
+// OsStr offers no unchecked conversion on other platforms on the minimum
+// supported compiler, so there is nothing to gain from caching.
+#[cfg(not(unix))]
+pub(crate) fn native_to_str(_index: usize, arg: &'static OsStr) -> Option<&'static str> {
+    arg.to_str()
+}
@@ -0,0 +1,88 @@
+use crate::once::OnceCell;
+use std::env;
+use std::ffi::OsStr;
+use std::slice;
+
+static VARS: OnceCell<Vec<(&OsStr, &OsStr)>> = OnceCell::new();
+
+/// Returns an iterator over the environment variables as `&'static str`
+/// pairs.
+///
+/// This is a replacement for `std::env::vars()` that produces the variables
+/// by reference instead of as owned strings. The environment is copied once,
+/// on the first call, and leaked; later calls produce that same snapshot, so
+/// changes made afterward by `std::env::set_var` and `remove_var` are not
+/// reflected. As with `std::env::vars()`, iterating past a variable whose
+/// name or value is not valid Unicode panics.
+pub fn vars() -> Vars {
+    Vars {
+        inner: snapshot().iter(),
+    }
+}
+
+// Looks up a variable in the same snapshot as produced by vars().
+pub(crate) fn var_os(name: &str) -> Option<&'static OsStr> {
+    snapshot()
+        .iter()
+        .find(|(key, _value)| same_name(key, name))
+        .map(|&(_key, value)| value)
+}
+
+#[cfg(not(windows))]
+fn same_name(key: &OsStr, name: &str) -> bool {
+    key == name
+}
+
+// Variable names are case insensitive on Windows.
+#[cfg(windows)]
+fn same_name(key: &OsStr, name: &str) -> bool {
+    key.to_str()
+        .map_or(false, |key| key.eq_ignore_ascii_case(name))
+}
+
+fn snapshot() -> &'static [(&'static OsStr, &'static OsStr)] {
+    VARS.get_or_init(|| {
+        crate::zero_alloc::exempt(|| {
+            env::vars_os()
+                .map(|(key, value)| -> (&OsStr, &OsStr) {
+                    (
+                        Box::leak(key.into_boxed_os_str()),
+                        Box::leak(value.into_boxed_os_str()),
+                    )
+                })
+                .collect()
+        })
+    })
+}
+
+/// Iterator over environment variables as `&'static str` pairs, returned by
+/// [`vars`].
+#[derive(Clone)]
+pub struct Vars {
+    inner: slice::Iter<'static, (&'static OsStr, &'static OsStr)>,
+}
+
+impl Iterator for Vars {
+    type Item = (&'static str, &'static str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &(key, value) = self.inner.next()?;
+        match (key.to_str(), value.to_str()) {
+            (Some(key), Some(value)) => Some((key, value)),
+            _ => panic!(
+                "environment variable is not valid Unicode: {:?}={:?}",
+                key, value,
+            ),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Vars {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
@@ -0,0 +1,192 @@
+//! Spawning copies of the current program as worker processes.
+//!
+//! A multi-process server typically starts as one supervisor process that
+//! runs several copies of its own binary, each told which worker it is.
+//! [`spawn`] runs the copies with the supervisor's own command line, plus
+//! arguments chosen for each worker appended at the end. In a worker, [`id`]
+//! and [`injected`] tell which worker it is and what was appended.
+//!
+//! ```no_run
+//! use std::io;
+//!
+//! fn main() -> io::Result<()> {
+//!     if let Some(id) = argv::worker::id() {
+//!         return run_worker(id);
+//!     }
+//!
+//!     let workers = argv::worker::spawn(4, |id| vec![format!("--worker-id={}", id)])?;
+//!     for mut worker in workers {
+//!         worker.wait()?;
+//!     }
+//!     Ok(())
+//! }
+//! #
+//! # fn run_worker(_id: usize) -> io::Result<()> {
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::once::OnceCell;
+use std::env;
+use std::ffi::OsStr;
+use std::io;
+use std::iter::Skip;
+use std::process::Child;
+
+const ID: &str = "ARGV_WORKER_ID";
+const INJECTED: &str = "ARGV_WORKER_INJECTED";
+
+/// Spawns `count` copies of the current program.
+///
+/// Each copy is started like [`argv::command()`][crate::command], with the
+/// arguments returned by `inject` for its index, from 0 to `count - 1`,
+/// appended to the command line. The index and the number of appended
+/// arguments are passed through environment variables, from which the worker
+/// reads them with [`id`] and [`injected`]. On Linux, Android, the BSDs and
+/// Windows, a static constructor in the worker reads the variables and
+/// removes them from its environment before main, so that processes it
+/// starts in turn are not mistaken for workers. Elsewhere, including macOS
+/// where this crate does not use static constructors, they remain in the
+/// worker's environment.
+///
+/// # Errors
+///
+/// Fails if the current executable cannot be located or any copy fails to
+/// spawn. Copies already spawned by then are killed and waited for.
+pub fn spawn<F, I>(count: usize, mut inject: F) -> io::Result<Vec<Child>>
+where
+    F: FnMut(usize) -> I,
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let mut children = Vec::with_capacity(count);
+    for id in 0..count {
+        let mut command = match crate::command() {
+            Ok(command) => command,
+            Err(err) => return Err(abort(children, err)),
+        };
+        let mut injected = 0;
+        for arg in inject(id) {
+            command.arg(arg);
+            injected += 1;
+        }
+        command
+            .env(ID, id.to_string())
+            .env(INJECTED, injected.to_string());
+        match command.spawn() {
+            Ok(child) => children.push(child),
+            Err(err) => return Err(abort(children, err)),
+        }
+    }
+    Ok(children)
+}
+
+fn abort(children: Vec<Child>, err: io::Error) -> io::Error {
+    for mut child in children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    err
+}
+
+/// Returns the index of this worker, if this process was started by
+/// [`spawn`].
+pub fn id() -> Option<usize> {
+    vars().id
+}
+
+/// Returns an iterator over the arguments that [`spawn`] appended to this
+/// worker's command line.
+///
+/// Produces nothing if this process was not started by `spawn`.
+pub fn injected() -> Injected {
+    let iter = crate::iter();
+    let skip = iter.len() - vars().injected.unwrap_or(0).min(iter.len());
+    Injected {
+        inner: iter.skip(skip),
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Vars {
+    id: Option<usize>,
+    injected: Option<usize>,
+}
+
+static VARS: OnceCell<Vars> = OnceCell::new();
+
+fn vars() -> Vars {
+    *VARS.get_or_init(|| Vars {
+        id: parse(ID),
+        injected: parse(INJECTED),
+    })
+}
+
+// Reads the variables and removes them from the environment, which would
+// otherwise be inherited by every process the worker starts. Runs as a static
+// constructor, while the program is still single-threaded, because modifying
+// the environment races with any other thread reading it. Processes that are
+// not workers have nothing to remove and leave the environment alone.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    windows,
+))]
+extern "C" fn capture() {
+    vars();
+    for var in &[ID, INJECTED] {
+        if env::var_os(var).is_some() {
+            env::remove_var(var);
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+#[link_section = ".init_array"]
+#[used]
+static CAPTURE: extern "C" fn() = capture;
+
+#[cfg(windows)]
+#[link_section = ".CRT$XCU"]
+#[used]
+static CAPTURE: extern "C" fn() = capture;
+
+fn parse(var: &str) -> Option<usize> {
+    env::var(var).ok()?.parse().ok()
+}
+
+/// Iterator over the arguments appended to a worker's command line, returned
+/// by [`injected`].
+#[derive(Clone)]
+pub struct Injected {
+    inner: Skip<crate::Iter>,
+}
+
+impl Iterator for Injected {
+    type Item = &'static OsStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Injected {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
@@ -0,0 +1,105 @@
+//! Enforcement of the allocation-free contract of the `zero-alloc` feature.
+//!
+//! When this crate is built with the `zero-alloc` feature, every operation
+//! after the initial capture of the arguments is allocation-free. Iteration,
+//! [`program`][crate::program], [`verify`][crate::verify],
+//! [`write_to_fd`][crate::write_to_fd] and the auxiliary vector accessors
+//! never allocate. Operations that would need to allocate, such as moving the
+//! arguments out of the way for [`title::set`][crate::title::set],
+//! [`scrub`][crate::scrub] and [`take_secret`][crate::take_secret], fail with
+//! an error of kind `Other` instead, and errors carry no message. Utilities
+//! whose purpose is to produce owned data, like [`title::get`][crate::title::get]
+//! and the [`redact`][crate::redact] module, are not covered.
+//!
+//! To have the contract checked, install [`CheckedAllocator`] as the global
+//! allocator. In builds with debug assertions it aborts the process on any allocation
+//! made from inside one of this crate's operations. The check keeps track of
+//! those operations in a thread local, whose first access on a thread may
+//! itself allocate when this crate is part of a shared library. The
+//! operations documented as safe to call from a signal handler,
+//! [`raw_iter`][crate::raw_iter], [`write_to_fd`][crate::write_to_fd] and
+//! [`annotation::get`][crate::annotation::get], are therefore not checked.
+//!
+//! ```
+//! use argv::zero_alloc::CheckedAllocator;
+//! use std::alloc::System;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CheckedAllocator<System> = CheckedAllocator(System);
+//! #
+//! # fn main() {}
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::process;
+
+thread_local! {
+    static DEPTH: Cell<usize> = Cell::new(0);
+}
+
+pub(crate) struct Guard {
+    _private: (),
+}
+
+// Marks the current thread as executing an operation that must not allocate.
+pub(crate) fn enter() -> Guard {
+    DEPTH.with(|depth| depth.set(depth.get() + 1));
+    Guard { _private: () }
+}
+
+// Runs the initial capture of the arguments, which is allowed to allocate.
+#[allow(dead_code)] // only the fallback implementation allocates lazily
+pub(crate) fn exempt<T>(f: impl FnOnce() -> T) -> T {
+    let saved = DEPTH.with(|depth| depth.replace(0));
+    let result = f();
+    DEPTH.with(|depth| depth.set(saved));
+    result
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Global allocator wrapper that detects allocations made by this crate in
+/// violation of the `zero-alloc` contract.
+///
+/// The check is a debug assertion; in release builds this forwards directly
+/// to the wrapped allocator. Because an allocator is not allowed to unwind, a
+/// violation aborts the process after printing a message to stderr.
+pub struct CheckedAllocator<A>(pub A);
+
+impl<A> CheckedAllocator<A> {
+    fn check() {
+        if cfg!(debug_assertions) && DEPTH.try_with(Cell::get).unwrap_or(0) > 0 {
+            let _ = DEPTH.try_with(|depth| depth.set(0));
+            let message = b"argv allocated memory despite the zero-alloc feature\n";
+            let _ = io::stderr().write_all(message);
+            process::abort();
+        }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CheckedAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::check();
+        unsafe { self.0.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.0.dealloc(ptr, layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::check();
+        unsafe { self.0.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::check();
+        unsafe { self.0.realloc(ptr, layout, new_size) }
+    }
+}
@@ -3,6 +3,7 @@ use std::process::Command;
 use target_triple::TARGET;
 
 #[test]
+#[cfg_attr(miri, ignore)] // spawns cargo
 fn test() {
     let output = Command::new("cargo")
         .args([
@@ -29,3 +30,458 @@ fn test() {
     let actual = String::from_utf8(output.stdout).unwrap();
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn test_args() {
+    let expected: Vec<String> = std::env::args().collect();
+    assert_eq!(argv::args().collect::<Vec<_>>(), expected);
+    assert_eq!(argv::args().len(), expected.len());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // current_exe is unsupported under isolation
+fn test_current_exe_static() {
+    let path = argv::current_exe_static().unwrap();
+    assert_eq!(path, std::env::current_exe().unwrap());
+    assert!(std::ptr::eq(path, argv::current_exe_static().unwrap()));
+}
+
+#[test]
+fn test_encoded_bytes() {
+    let bytes: Vec<&[u8]> = argv::encoded_bytes().collect();
+    assert_eq!(bytes.len(), argv::iter().len());
+    for (bytes, arg) in bytes.into_iter().zip(argv::iter()) {
+        assert_eq!(argv::bytes::to_os_str(bytes).unwrap(), arg);
+    }
+}
+
+#[test]
+#[cfg(windows)]
+fn test_to_os_str_surrogates() {
+    // A lone surrogate is WTF-8, a pair encoded as two surrogates is not.
+    assert!(argv::bytes::to_os_str(b"\xED\xA0\xBD").is_some());
+    assert!(argv::bytes::to_os_str(b"\xED\xA0\xBD\xED\xB8\x80").is_none());
+    assert!(argv::bytes::to_os_str(b"\xED\xB8\x80\xED\xA0\xBD").is_some());
+}
+
+#[test]
+fn test_vars() {
+    let expected: Vec<(String, String)> = std::env::vars().collect();
+    let vars: Vec<(&str, &str)> = argv::vars().collect();
+    assert_eq!(vars.len(), expected.len());
+    for (key, value) in &expected {
+        assert!(vars.contains(&(key, value)));
+    }
+}
+
+#[test]
+fn test_secure_exec() {
+    // The test harness is not installed setuid or setgid.
+    assert!(!argv::is_secure_exec());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[cfg_attr(miri, ignore)] // no auxiliary vector
+fn test_auxv() {
+    let page_size = argv::auxv::page_size().unwrap();
+    assert!(page_size.is_power_of_two());
+    assert!(argv::auxv::platform().is_some());
+    assert_eq!(argv::auxv::uid(), argv::auxv::euid());
+    assert_eq!(argv::auxv::get(0xdead_beef), None);
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn test_apple() {
+    assert!(argv::apple::executable_path().is_some());
+}
+
+#[cfg(all(
+    target_os = "linux",
+    not(target_env = "musl"),
+    not(feature = "force-fallback"),
+    not(miri),
+))]
+#[test]
+fn test_stack_region() {
+    let region = argv::stack_region().unwrap();
+    let len = region.end as usize - region.start as usize;
+    let bytes = unsafe { std::slice::from_raw_parts(region.start, len) };
+    let mut strings = bytes[..len - 1].split(|&b| b == b'\0');
+    for arg in argv::iter() {
+        let arg = std::os::unix::ffi::OsStrExt::as_bytes(arg);
+        assert_eq!(strings.next(), Some(arg));
+    }
+}
+
+#[cfg(all(
+    target_os = "linux",
+    not(target_env = "musl"),
+    not(feature = "force-fallback"),
+    not(miri),
+))]
+#[test]
+fn test_raw_region() {
+    let (start, len) = argv::raw_region().unwrap();
+    let bytes = unsafe { std::slice::from_raw_parts(start, len) };
+    let args: Vec<&[u8]> = argv::iter()
+        .map(std::os::unix::ffi::OsStrExt::as_bytes)
+        .collect();
+    let strings: Vec<&[u8]> = bytes[..len - 1].split(|&b| b == b'\0').collect();
+    assert_eq!(strings, args);
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+#[cfg_attr(miri, ignore)] // no glibc globals
+fn test_program_invocation_name() {
+    let argv0 = argv::iter().next();
+    assert_eq!(argv::program_invocation_name(), argv0);
+    let short_name = argv::program_invocation_short_name().unwrap();
+    assert!(argv0
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .ends_with(short_name.to_str().unwrap()));
+}
+
+#[test]
+fn test_audit() {
+    use argv::redact::Redactor;
+    use std::ffi::OsStr;
+
+    let args = [
+        "prog",
+        "--password",
+        "hunter2",
+        "--token=abc",
+        "--user=bob",
+        "ghp_0123456789abcdef",
+        "MY_API_KEY=xyz",
+    ];
+    let mut findings = Vec::new();
+    argv::audit::check(
+        &Redactor::with_defaults(),
+        args.iter().map(OsStr::new),
+        |finding| findings.push((finding.index(), finding.reason().to_owned())),
+    );
+    assert_eq!(
+        findings,
+        [
+            (2, "--password".to_owned()),
+            (3, "--token".to_owned()),
+            (5, "GitHub token".to_owned()),
+            (6, "MY_API_KEY".to_owned()),
+        ],
+    );
+}
+
+#[test]
+fn test_raw_iter() {
+    let args: Vec<_> = argv::iter().collect();
+    let raw: Vec<_> = argv::raw_iter().collect();
+    assert_eq!(args, raw);
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg_attr(miri, ignore)] // creates a pipe
+fn test_write_to_fd() {
+    use std::fs::{self, File};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::io::AsRawFd;
+
+    let mut expected = Vec::new();
+    for arg in argv::iter() {
+        expected.extend_from_slice(arg.as_bytes());
+        expected.push(b'\0');
+    }
+    let path = std::env::temp_dir().join(format!("argv-test-{}", std::process::id()));
+    let file = File::create(&path).unwrap();
+    argv::write_to_fd(file.as_raw_fd(), b'\0').unwrap();
+    drop(file);
+    let written = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(written, expected);
+}
+
+#[test]
+fn test_annotation() {
+    use argv::redact::Redactor;
+
+    let redactor = Redactor::new();
+    let full = redactor.display(argv::iter()).to_string();
+
+    let mut buf = [0; 8];
+    let len = argv::annotation::format_into(&mut buf, &redactor);
+    assert_eq!(&buf[..len], &full.as_bytes()[..len]);
+
+    let annotation = argv::annotation::install(&redactor);
+    assert_eq!(argv::annotation::get(), Some(annotation));
+    assert!(full.as_bytes().starts_with(annotation));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // reads the environment and filesystem
+fn test_resolve_program_path() {
+    // Cargo runs the test binary by its path.
+    let argv0 = argv::iter().next().unwrap();
+    assert_eq!(
+        argv::resolve_program_path(),
+        Some(std::path::Path::new(argv0))
+    );
+}
+
+#[test]
+fn test_program() {
+    assert_eq!(Some(argv::program()), argv::iter().next());
+}
+
+#[test]
+fn test_verify() {
+    assert!(argv::verify());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[cfg_attr(miri, ignore)] // reads procfs
+fn test_of_pid() {
+    use std::io::Read as _;
+    use std::process::{Command, Stdio};
+
+    let current = argv::of_pid(std::process::id()).unwrap();
+    let expected: Vec<_> = std::env::args_os().collect();
+    assert_eq!(current, expected);
+
+    let mut child = Command::new("sh")
+        .args(&["-c", "echo; read line", "sh", ""])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Wait for the shell to start up.
+    let mut newline = [0];
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_exact(&mut newline)
+        .unwrap();
+    let args = argv::of_pid(child.id()).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    // A trailing empty argument is indistinguishable from padding.
+    assert_eq!(args, ["sh", "-c", "echo; read line", "sh"]);
+
+    assert!(!argv::parent().unwrap().is_empty());
+
+    let err = argv::of_pid(u32::MAX).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[cfg_attr(miri, ignore)] // reads procfs
+fn test_processes() {
+    let pid = std::process::id();
+    let expected: Vec<_> = std::env::args_os().collect();
+    let mut found = false;
+    for (other, args) in argv::processes().unwrap() {
+        if other == pid {
+            assert_eq!(args, expected);
+            found = true;
+        }
+    }
+    assert!(found);
+}
+
+#[test]
+fn test_libtest_strip() {
+    // Whatever flags and filters this test binary was run with.
+    let args = argv::libtest::strip(argv::iter());
+    assert_eq!(args, argv::iter().take(1).collect::<Vec<_>>());
+
+    let args = [
+        "test-bin",
+        "--test-threads=2",
+        "--skip",
+        "slow",
+        "-Zunstable-options",
+        "--format",
+        "json",
+        "--custom",
+        "filter",
+    ];
+    assert_eq!(argv::libtest::strip(args), ["test-bin", "--custom"]);
+}
+
+#[test]
+fn test_c_strs() {
+    use std::ffi::{CStr, OsStr};
+
+    let c_strs: Vec<&CStr> = argv::c_strs().collect();
+    let iter: Vec<&OsStr> = argv::iter().collect();
+    assert_eq!(c_strs.len(), iter.len());
+    for (c_str, arg) in c_strs.iter().zip(iter) {
+        assert_eq!(c_str.to_string_lossy(), arg.to_string_lossy());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_iter_c_ptrs() {
+    use std::ffi::CStr;
+
+    let ptrs: Vec<_> = argv::iter_c_ptrs().collect();
+    let c_strs: Vec<&CStr> = argv::c_strs().collect();
+    assert_eq!(ptrs.len(), c_strs.len());
+    for (&ptr, c_str) in ptrs.iter().zip(c_strs) {
+        assert_eq!(unsafe { CStr::from_ptr(ptr) }, c_str);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_reexec_with() {
+    let mut reexec = argv::reexec_with();
+    let original: Vec<_> = argv::iter().collect();
+    assert_eq!(reexec.get_args().collect::<Vec<_>>(), original);
+
+    reexec
+        .arg("--x")
+        .arg("--y")
+        .arg("--x")
+        .remove("--x")
+        .replace("--y", "--z");
+    let args: Vec<_> = reexec.get_args().collect();
+    assert_eq!(args[..original.len()], original[..]);
+    assert_eq!(args[original.len()..], ["--z"]);
+
+    let err = reexec.arg("nul\0").exec();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // current_exe
+fn test_command() {
+    let command = argv::command().unwrap();
+    assert_eq!(command.get_program(), argv::current_exe_static().unwrap());
+    let args: Vec<_> = command.get_args().collect();
+    assert_eq!(args, argv::iter().skip(1).collect::<Vec<_>>());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // calls sysconf
+fn test_arg_max() {
+    use std::ffi::OsStr;
+
+    if cfg!(any(unix, windows)) {
+        let arg_max = argv::arg_max().unwrap();
+        let budget = argv::arg_budget().unwrap();
+        assert!(budget < arg_max);
+    }
+    assert!(argv::arg_cost(OsStr::new("abc")) > 3);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+#[cfg_attr(miri, ignore)] // reads procfs
+fn test_verify_against_procfs() {
+    assert_eq!(argv::verify_against_procfs().unwrap(), None);
+}
+
+#[test]
+fn test_display() {
+    use std::ffi::OsStr;
+
+    let arg = OsStr::new("tab\there\\ é\u{1b}[31m\n");
+    assert_eq!(
+        argv::display(arg).to_string(),
+        "tab\\there\\\\ é\\u{1b}[31m\\n",
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let arg = OsStr::from_bytes(b"a\xffb\xe2\x82");
+        assert_eq!(argv::display(arg).to_string(), "a\\xffb\\xe2\\x82");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_debug_dump() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut dump = Vec::new();
+    argv::debug_dump(&mut dump).unwrap();
+    let dump = String::from_utf8(dump).unwrap();
+    assert_eq!(dump.lines().count(), argv::iter().len());
+    let program = argv::iter().next().unwrap();
+    assert!(dump.starts_with(&format!("argv[0]: {} bytes, ", program.as_bytes().len())));
+}
+
+#[test]
+fn test_spans() {
+    let spans = match argv::spans() {
+        Some(spans) => spans,
+        None => {
+            let native = cfg!(all(
+                target_os = "linux",
+                not(target_env = "musl"),
+                not(feature = "force-fallback"),
+                not(miri),
+            ));
+            assert!(!native);
+            return;
+        }
+    };
+    assert_eq!(spans.len(), argv::iter().len());
+    assert_eq!(spans[0].offset(), 0);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let (start, len) = argv::raw_region().unwrap();
+        let raw = unsafe { std::slice::from_raw_parts(start, len) };
+        for (arg, span) in argv::iter().zip(spans) {
+            assert_eq!(&raw[span.range()], arg.as_bytes());
+            assert_eq!(raw[span.range().end], b'\0');
+        }
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // leaks memory
+fn test_leak_args() {
+    use std::ffi::CStr;
+
+    let argv = argv::leak_args(&["sh", "-c", "echo hello", ""]).unwrap();
+    let args: Vec<&CStr> = (0..)
+        .map(|i| unsafe { *argv.add(i) })
+        .take_while(|ptr| !ptr.is_null())
+        .map(|ptr| unsafe { CStr::from_ptr(ptr) })
+        .collect();
+    assert_eq!(args.len(), 4);
+    assert_eq!(args[2].to_bytes(), b"echo hello");
+    assert_eq!(args[3].to_bytes(), b"");
+
+    assert!(argv::leak_args(&["a\0b"]).is_err());
+}
+
+#[test]
+fn test_as_exec_array() {
+    use std::ffi::CStr;
+
+    let argv = argv::as_exec_array();
+    let args: Vec<&CStr> = (0..)
+        .map(|i| unsafe { *argv.add(i) })
+        .take_while(|ptr| !ptr.is_null())
+        .map(|ptr| unsafe { CStr::from_ptr(ptr) })
+        .collect();
+    assert_eq!(args, argv::c_strs().collect::<Vec<_>>());
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "c-abi")]
+
+use std::ffi::{CStr, OsString};
+use std::os::raw::{c_char, c_int};
+
+extern "C" {
+    fn rust_argv_argc() -> c_int;
+    fn rust_argv_argv() -> *const *const c_char;
+}
+
+#[test]
+fn test_c_abi() {
+    let argc = unsafe { rust_argv_argc() } as usize;
+    let argv = unsafe { rust_argv_argv() };
+
+    let expected: Vec<OsString> = std::env::args_os().collect();
+    assert_eq!(argc, expected.len());
+    assert_eq!(argc, argv::iter().len());
+    for (i, expected) in expected.iter().enumerate() {
+        let arg = unsafe { CStr::from_ptr(*argv.add(i)) };
+        assert_eq!(arg.to_string_lossy(), expected.to_string_lossy());
+    }
+    assert!(unsafe { *argv.add(argc) }.is_null());
+}
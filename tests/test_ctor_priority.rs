@@ -0,0 +1,29 @@
+#![cfg(all(
+    feature = "ctor-priority-1000",
+    target_os = "linux",
+    not(target_env = "musl"),
+    not(feature = "force-fallback"),
+    not(miri),
+))]
+
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static LEN_IN_CONSTRUCTOR: AtomicUsize = AtomicUsize::new(0);
+
+// Runs after argv's constructor at priority 1000, but before any constructor
+// without a priority.
+#[link_section = ".init_array.01001"]
+#[used]
+static CONSTRUCTOR: extern "C" fn(c_int) = constructor;
+
+extern "C" fn constructor(_argc: c_int) {
+    LEN_IN_CONSTRUCTOR.store(argv::iter().len(), Ordering::Relaxed);
+}
+
+#[test]
+fn test_iter_in_constructor() {
+    let len = LEN_IN_CONSTRUCTOR.load(Ordering::Relaxed);
+    assert_ne!(len, 0);
+    assert_eq!(len, std::env::args_os().len());
+}
@@ -0,0 +1,14 @@
+#![cfg(feature = "debug-check")]
+
+use std::env;
+use std::ffi::OsString;
+
+#[test]
+fn test_debug_check() {
+    // Must happen before anything in this test binary calls argv::iter().
+    env::set_var("ARGV_DEBUG_CHECK", "panic");
+
+    let expected: Vec<OsString> = env::args_os().collect();
+    let args: Vec<OsString> = argv::iter().map(OsString::from).collect();
+    assert_eq!(args, expected);
+}
@@ -0,0 +1,45 @@
+#![cfg(all(target_os = "linux", target_env = "gnu"))]
+
+use std::ffi::CString;
+use std::io::{self, Write};
+use std::os::raw::{c_char, c_int, c_void};
+use std::process::Command;
+use target_triple::TARGET;
+
+#[link(name = "dl")]
+extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+const RTLD_NOW: c_int = 2;
+
+#[test]
+#[cfg_attr(miri, ignore)] // spawns cargo
+fn test_dlopen() {
+    let output = Command::new("cargo")
+        .args(["build", "--example", "plugin", "--target", TARGET])
+        .output()
+        .expect("failed to execute process");
+    io::stderr().lock().write_all(&output.stderr).unwrap();
+    assert!(output.status.success());
+
+    let library = CString::new(format!("target/{TARGET}/debug/examples/libplugin.so")).unwrap();
+    let handle = unsafe { dlopen(library.as_ptr(), RTLD_NOW) };
+    assert!(!handle.is_null());
+    let symbol = unsafe { dlsym(handle, b"plugin_args\0".as_ptr().cast()) };
+    assert!(!symbol.is_null());
+    let plugin_args: unsafe extern "C" fn(*mut c_char, usize) -> usize =
+        unsafe { std::mem::transmute(symbol) };
+
+    let len = unsafe { plugin_args(std::ptr::null_mut(), 0) };
+    let mut buf = vec![0u8; len];
+    unsafe { plugin_args(buf.as_mut_ptr().cast(), len) };
+
+    let mut expected = Vec::new();
+    for arg in std::env::args() {
+        expected.extend_from_slice(arg.as_bytes());
+        expected.push(0);
+    }
+    assert_eq!(buf, expected);
+}
@@ -0,0 +1,10 @@
+#[test]
+fn test_set_logical_args() {
+    argv::embed::set_logical_args(&["plugin", "--level", "3"]).unwrap();
+    assert_eq!(argv::iter().collect::<Vec<_>>(), ["plugin", "--level", "3"],);
+    assert_eq!(argv::program(), "plugin");
+
+    let err = argv::embed::set_logical_args(&["again"]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(argv::iter().len(), 3);
+}
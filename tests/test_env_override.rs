@@ -0,0 +1,18 @@
+#![cfg(feature = "mock")]
+
+use std::env;
+
+#[test]
+fn test_env_override() {
+    // Must happen before anything in this test binary calls argv::iter().
+    env::set_var("ARGV_OVERRIDE", "prog\n--flag\nvalue\n");
+
+    assert_eq!(
+        argv::iter().collect::<Vec<_>>(),
+        ["prog", "--flag", "value"]
+    );
+    argv::mock::with_args(&["inner"], || {
+        assert_eq!(argv::iter().collect::<Vec<_>>(), ["inner"]);
+    });
+    assert_eq!(argv::program(), "prog");
+}
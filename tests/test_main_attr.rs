@@ -0,0 +1,11 @@
+use std::ffi::OsString;
+
+#[argv::main]
+fn main(args: argv::Iter) -> Result<(), String> {
+    let args: Vec<OsString> = args.map(OsString::from).collect();
+    if args == std::env::args_os().collect::<Vec<_>>() {
+        Ok(())
+    } else {
+        Err(format!("{:?}", args))
+    }
+}
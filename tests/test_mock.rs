@@ -0,0 +1,265 @@
+#![cfg(feature = "mock")]
+
+use std::ffi::OsStr;
+
+#[test]
+fn test_with_args() {
+    let real: Vec<&OsStr> = argv::iter().collect();
+
+    let result = argv::mock::with_args(&["prog", "--flag"], || {
+        assert_eq!(argv::iter().collect::<Vec<_>>(), ["prog", "--flag"]);
+        assert_eq!(argv::program(), "prog");
+
+        argv::mock::with_args(Vec::<String>::new(), || {
+            assert_eq!(argv::iter().len(), 0);
+        });
+        assert_eq!(argv::iter().len(), 2);
+        1
+    });
+    assert_eq!(result, 1);
+
+    assert_eq!(argv::iter().collect::<Vec<_>>(), real);
+}
+
+#[test]
+fn test_set_override() {
+    argv::mock::with_args(argv::iter(), || {
+        argv::mock::set_override(&["prog", "--global"]);
+        assert_eq!(argv::iter().collect::<Vec<_>>(), ["prog", "--global"]);
+        argv::mock::clear_override();
+        assert!(argv::mock::with_args(&["a"], || argv::iter().len()) == 1);
+    });
+}
+
+#[test]
+fn test_with_thread_args() {
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            std::thread::spawn(move || {
+                let flag = format!("--thread={}", i);
+                argv::mock::with_thread_args(&["prog", &flag], || {
+                    for _ in 0..100 {
+                        assert_eq!(argv::iter().nth(1).unwrap(), &*flag);
+                        std::thread::yield_now();
+                    }
+                });
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn test_fuzz_install() {
+    argv::mock::with_args(argv::iter(), || {
+        argv::mock::clear_override();
+        for input in [&["prog", "-x"][..], &["prog"], &["prog", "--long", "value"]] {
+            let args: Vec<&OsStr> = input.iter().map(OsStr::new).collect();
+            unsafe { argv::fuzz::install(&args) };
+            assert_eq!(argv::iter().collect::<Vec<_>>(), args);
+        }
+        unsafe { argv::fuzz::uninstall() };
+    });
+}
+
+#[test]
+fn test_record_replay() {
+    let real: Vec<&OsStr> = argv::iter().collect();
+    let path = std::env::temp_dir().join(format!("argv-record-{}", std::process::id()));
+
+    argv::mock::with_args(&["prog", "", "two words", "ünïcode"], || {
+        argv::record_to(&path).unwrap();
+        argv::mock::clear_override();
+        argv::replay_from(&path).unwrap();
+        assert_eq!(
+            argv::iter().collect::<Vec<_>>(),
+            ["prog", "", "two words", "ünïcode"],
+        );
+    });
+    assert_eq!(argv::iter().collect::<Vec<_>>(), real);
+
+    std::fs::write(&path, b"truncated").unwrap();
+    let err = argv::replay_from(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg_attr(miri, ignore)] // calls getopt from libc
+fn test_getopt() {
+    use argv::getopt::Argv;
+    use std::os::raw::{c_char, c_int};
+
+    extern "C" {
+        fn getopt(argc: c_int, argv: *const *mut c_char, optstring: *const c_char) -> c_int;
+    }
+
+    argv::mock::with_args(&["prog", "input", "-v", "-o", "out", "rest"], || {
+        let mut argv = Argv::new();
+        let mut options = Vec::new();
+        argv.run(|argc, ptr| loop {
+            match unsafe { getopt(argc, ptr, b"vo:\0".as_ptr().cast()) } {
+                -1 => break,
+                option => options.push(option as u8),
+            }
+        });
+        assert_eq!(options, b"vo");
+
+        let mut operands = argv.operands();
+        operands.sort_unstable();
+        assert_eq!(operands, [1, 5]);
+        let mut consumed = argv.consumed();
+        consumed.sort_unstable();
+        assert_eq!(consumed, [0, 2, 3, 4]);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn test_lossy() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let args = [
+        OsStr::new("prog"),
+        OsStr::from_bytes(b"a\xFFb\xF0\x9F"),
+        OsStr::new("ok"),
+    ];
+    argv::mock::with_args(&args, || {
+        let lossy = argv::lossy();
+        assert!(!lossy.is_lossless());
+        assert_eq!(lossy.args(), ["prog", "a\u{FFFD}b\u{FFFD}", "ok"]);
+        assert_eq!(lossy.replacements().len(), 1);
+        assert_eq!(lossy.replacements()[0].index(), 1);
+        assert_eq!(lossy.replacements()[0].count(), 3);
+    });
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn test_nfc() {
+    use std::borrow::Cow;
+
+    argv::mock::with_args(&["prog", "cafe\u{301}", "caf\u{e9}"], || {
+        let args: Vec<Cow<OsStr>> = argv::nfc::iter().collect();
+        assert_eq!(
+            args,
+            [
+                OsStr::new("prog"),
+                OsStr::new("caf\u{e9}"),
+                OsStr::new("caf\u{e9}")
+            ]
+        );
+        assert!(matches!(args[0], Cow::Borrowed(_)));
+        assert!(matches!(args[1], Cow::Owned(_)));
+        assert!(matches!(args[2], Cow::Borrowed(_)));
+    });
+}
+
+#[test]
+fn test_stats() {
+    argv::mock::with_args(&["prog", "", "longest"], || {
+        let stats = argv::stats();
+        assert_eq!(stats.count(), 3);
+        assert_eq!(stats.total_bytes(), 11);
+        assert_eq!(stats.longest(), 7);
+        assert_eq!(argv::total_bytes(), 11);
+    });
+}
+
+#[test]
+fn test_first_n() {
+    argv::mock::with_args(&["prog", "in", "out"], || {
+        let [_, input, output] = argv::first_n::<3>().unwrap();
+        assert_eq!(input, "in");
+        assert_eq!(output, "out");
+        assert_eq!(argv::first_n::<2>().unwrap(), ["prog", "in"]);
+        assert!(argv::first_n::<4>().is_none());
+        assert_eq!(argv::first_n::<0>().unwrap().len(), 0);
+
+        let ([prog], rest) = argv::split_first_n::<1>().unwrap();
+        assert_eq!(prog, "prog");
+        assert_eq!(rest.collect::<Vec<_>>(), ["in", "out"]);
+    });
+}
+
+#[test]
+fn test_find() {
+    argv::mock::with_args(&["prog", "-v", "--", "-v"], || {
+        assert_eq!(argv::position_of("-v"), Some(1));
+        assert_eq!(argv::position_of("--"), Some(2));
+        assert_eq!(argv::position_of("-q"), None);
+        assert_eq!(argv::find(|arg| arg.len() == 2), Some(OsStr::new("-v")));
+        assert_eq!(argv::find(|arg| arg.is_empty()), None);
+    });
+
+    argv::mock::with_args(&["prog", "--url", "https://example.com"], || {
+        assert_eq!(argv::find_containing(b"://"), Some((2, 5)));
+        assert_eq!(argv::find_containing(b"-u"), Some((1, 1)));
+        assert_eq!(argv::find_containing(b""), Some((0, 0)));
+        assert_eq!(argv::find_containing(b"ftp"), None);
+    });
+}
+
+#[test]
+fn test_debug_dump() {
+    argv::mock::with_args(&["prog", "two words", ""], || {
+        let mut dump = Vec::new();
+        argv::debug_dump(&mut dump).unwrap();
+        assert_eq!(
+            String::from_utf8(dump).unwrap(),
+            "argv[0]: 4 bytes, \"prog\"\nargv[1]: 9 bytes, \"two words\"\nargv[2]: 0 bytes, \"\"\n",
+        );
+    });
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let args = [OsStr::from_bytes(b"caf\xe9")];
+        argv::mock::with_args(&args, || {
+            let mut dump = Vec::new();
+            argv::debug_dump(&mut dump).unwrap();
+            assert_eq!(
+                String::from_utf8(dump).unwrap(),
+                "argv[0]: 4 bytes, \"caf\u{fffd}\", not UTF-8: 63 61 66 e9\n",
+            );
+        });
+    }
+}
+
+#[test]
+fn test_underline() {
+    argv::mock::with_args(&["tool", "--frobnicate", "two words", "it's"], || {
+        let marker = argv::underline(1).unwrap();
+        assert_eq!(
+            marker.to_string(),
+            "tool --frobnicate 'two words' 'it'\\''s'\n     ^^^^^^^^^^^^",
+        );
+        let marker = argv::underline(2).unwrap();
+        assert_eq!(marker.columns(), 18..29);
+        let marker = argv::underline_range(3, 2..4).unwrap();
+        assert_eq!(marker.line(), "tool --frobnicate 'two words' 'it'\\''s'");
+        assert_eq!(marker.columns(), 33..38);
+        let marker = argv::underline_range(1, 2..2).unwrap();
+        assert!(marker.to_string().ends_with("\n       ^"));
+
+        assert!(argv::underline(4).is_none());
+        assert!(argv::underline_range(1, 5..20).is_none());
+    });
+}
+
+#[test]
+fn test_report() {
+    argv::mock::with_args(&["prog", "--token", "abc", "two words"], || {
+        let report = argv::report().to_string();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("program: "));
+        assert_eq!(lines[1], "arguments: prog --token '***' 'two words'");
+        assert_eq!(lines[2], format!("platform: {}", target_triple::TARGET));
+        assert_eq!(lines[3], "capture source: mock");
+    });
+}
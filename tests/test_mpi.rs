@@ -0,0 +1,41 @@
+#![cfg(all(unix, feature = "mock"))]
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+// Removes arguments starting with "-mca", the way MPI implementations remove
+// their own options.
+unsafe fn fake_mpi_init(argc: *mut c_int, argv: *mut *mut *mut c_char) -> c_int {
+    let args = unsafe { *argv };
+    let mut kept = 0;
+    for i in 0..unsafe { *argc } as usize {
+        let arg = unsafe { *args.add(i) };
+        if !unsafe { CStr::from_ptr(arg) }
+            .to_bytes()
+            .starts_with(b"-mca")
+        {
+            unsafe { *args.add(kept) = arg };
+            kept += 1;
+        }
+    }
+    unsafe {
+        *args.add(kept) = std::ptr::null_mut();
+        *argc = kept as c_int;
+    }
+    0
+}
+
+#[test]
+fn test_mpi_init() {
+    let rc = argv::mock::with_args(&["solver", "-mca-btl", "--steps", "-mca-x", "10"], || {
+        argv::mpi::init(|argc, argv| unsafe { fake_mpi_init(argc, argv) }).unwrap()
+    });
+    assert_eq!(rc, 0);
+    assert_eq!(
+        argv::iter().collect::<Vec<_>>(),
+        ["solver", "--steps", "10"],
+    );
+
+    let err = argv::mpi::init(|_argc, _argv| unreachable!()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+}
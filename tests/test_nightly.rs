@@ -0,0 +1,16 @@
+#![cfg(feature = "nightly")]
+#![feature(trusted_len)]
+
+use std::iter::TrustedLen;
+
+#[test]
+fn test_trusted_len() {
+    fn assert_trusted_len<T: TrustedLen>(iter: T) -> T {
+        iter
+    }
+
+    let iter = assert_trusted_len(argv::iter());
+    let (lower, upper) = iter.size_hint();
+    assert_eq!(Some(lower), upper);
+    assert_eq!(iter.collect::<Vec<_>>().len(), lower);
+}
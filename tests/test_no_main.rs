@@ -0,0 +1,18 @@
+#![cfg_attr(unix, no_main)]
+
+#[cfg(unix)]
+argv::no_main!(run);
+
+#[cfg(unix)]
+fn run(args: &'static [&'static std::ffi::OsStr]) -> i32 {
+    assert_eq!(args, argv::iter().collect::<Vec<_>>());
+    // musl does not pass the arguments to the standard library's initializer
+    // in a binary without the standard library's main.
+    if !cfg!(miri) && !cfg!(target_env = "musl") {
+        assert_eq!(args, std::env::args_os().collect::<Vec<_>>());
+    }
+    0
+}
+
+#[cfg(not(unix))]
+fn main() {}
@@ -0,0 +1,24 @@
+use std::io::{self, Write};
+
+fn main() {
+    if argv::test::is_child() {
+        argv::install_panic_hook();
+        panic!("oops");
+    }
+
+    if cfg!(miri) {
+        return;
+    }
+
+    let output = argv::test::spawn_self(&["prog", "--password", "hunter2", "two words"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    io::stderr().lock().write_all(stderr.as_bytes()).unwrap();
+    assert!(stderr.contains("oops"));
+    let last = stderr.lines().last().unwrap();
+    if cfg!(unix) {
+        assert_eq!(last, "command line: prog --password '***' 'two words'");
+    } else {
+        assert!(last.ends_with(" --password '***' 'two words'"));
+    }
+}
@@ -0,0 +1,27 @@
+#![cfg(all(target_os = "linux", target_env = "gnu"))]
+
+use std::io::{self, Write};
+use std::process::Command;
+use target_triple::TARGET;
+
+#[test]
+#[cfg_attr(miri, ignore)] // spawns cargo
+fn test_preload() {
+    let output = Command::new("cargo")
+        .args(["build", "--example", "preload", "--target", TARGET])
+        .output()
+        .expect("failed to execute process");
+    io::stderr().lock().write_all(&output.stderr).unwrap();
+    assert!(output.status.success());
+
+    let library = format!("target/{TARGET}/debug/examples/libpreload.so");
+    let output = Command::new("true")
+        .args(["--flag", "two words"])
+        .env("LD_PRELOAD", library)
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr, "argv: true --flag two words\n");
+}
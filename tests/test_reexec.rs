@@ -0,0 +1,42 @@
+use std::env;
+use std::io::{self, Write};
+
+const REEXECUTED: &str = "ARGV_TEST_REEXECUTED";
+
+fn main() {
+    if argv::test::is_child() {
+        if env::var_os(REEXECUTED).is_none() {
+            env::set_var(REEXECUTED, "1");
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            {
+                if argv::iter().any(|arg| arg == "--proc-self-exe") {
+                    panic!("{}", argv::reexec_proc_self_exe());
+                }
+            }
+            #[cfg(unix)]
+            panic!("{}", argv::reexec());
+        }
+        let mut stdout = io::stdout().lock();
+        for arg in argv::iter() {
+            writeln!(stdout, "{}", arg.to_string_lossy()).unwrap();
+        }
+        return;
+    }
+
+    if cfg!(any(miri, not(unix))) {
+        return;
+    }
+
+    check(&["prog", "two words", "--flag"]);
+    if cfg!(any(target_os = "linux", target_os = "android")) {
+        check(&["prog", "--proc-self-exe"]);
+    }
+}
+
+fn check(args: &[&str]) {
+    let output = argv::test::spawn_self(args);
+    io::stderr().lock().write_all(&output.stderr).unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), args);
+}
@@ -0,0 +1,90 @@
+use std::ffi::OsStr;
+use std::io::{self, Write};
+use std::os::raw::c_char;
+
+fn main() {
+    if argv::test::is_child() {
+        child();
+        return;
+    }
+
+    if cfg!(miri) {
+        return;
+    }
+
+    #[cfg(unix)]
+    let invalid = {
+        use std::os::unix::ffi::OsStrExt;
+        OsStr::from_bytes(b"\xffbad")
+    };
+    #[cfg(not(unix))]
+    let invalid = OsStr::new("bad");
+
+    let args = [
+        OsStr::new("prog"),
+        OsStr::new("--gtk-flag"),
+        invalid,
+        OsStr::new("keep"),
+    ];
+    let output = argv::test::spawn_self(&args);
+    io::stderr().lock().write_all(&output.stderr).unwrap();
+    assert!(output.status.success());
+}
+
+fn child() {
+    let before: Vec<_> = argv::iter().collect();
+    let array = argv::as_exec_array() as *mut *const c_char;
+
+    let native = cfg!(all(
+        target_os = "linux",
+        not(target_env = "musl"),
+        not(feature = "force-fallback"),
+    ));
+    if !native {
+        assert!(argv::refresh().is_err());
+        return;
+    }
+
+    // Validates every argument as UTF-8, which is remembered per string.
+    let lossy = argv::lossy();
+    assert_eq!(lossy.replacements()[0].index(), 2);
+    assert_eq!(argv::args().take(2).count(), 2);
+    let mut stale_args = argv::args();
+    let stale_iter = argv::iter();
+    let stale_c_strs = argv::c_strs();
+
+    // What a C library does to remove the argument it recognized.
+    unsafe {
+        *array.add(1) = *array.add(2);
+        *array.add(2) = *array.add(3);
+        *array.add(3) = std::ptr::null();
+    }
+    argv::refresh().unwrap();
+
+    let after: Vec<_> = argv::iter().collect();
+    assert_eq!(after.len(), 3);
+    assert_eq!(after[0], before[0]);
+    assert_eq!(after[1], before[2]);
+    assert_eq!(after[2], "keep");
+    assert_eq!(before[1], "--gtk-flag");
+    assert!(argv::verify());
+    assert_eq!(argv::spans(), None);
+
+    // Iterators created before refresh() keep their length and produce an
+    // empty string past the new end of the array.
+    assert_eq!(stale_iter.len(), 4);
+    let stale: Vec<_> = stale_iter.collect();
+    assert_eq!(stale[..3], after[..]);
+    assert_eq!(stale[3], "");
+    let stale_c_strs: Vec<_> = stale_c_strs.collect();
+    assert_eq!(stale_c_strs.len(), 4);
+    assert_eq!(stale_c_strs[2].to_bytes(), b"keep");
+    assert!(stale_c_strs[3].to_bytes().is_empty());
+    assert_eq!(stale_args.next(), before[0].to_str());
+
+    let lossy = argv::lossy();
+    assert_eq!(lossy.args()[1], "\u{fffd}bad");
+    assert_eq!(lossy.replacements().len(), 1);
+    assert_eq!(lossy.replacements()[0].index(), 1);
+    assert_eq!(lossy.args()[2], "keep");
+}
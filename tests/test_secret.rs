@@ -0,0 +1,35 @@
+#![cfg(all(
+    target_os = "linux",
+    target_env = "gnu",
+    not(feature = "zero-alloc"),
+    not(feature = "force-fallback"),
+    not(miri),
+))]
+
+use std::ffi::OsString;
+use std::fs;
+
+// Kept as the only test in this binary because secrets can only be taken
+// before anything else in the process calls argv::iter().
+#[test]
+fn test_take_secret() {
+    let original: Vec<OsString> = std::env::args_os().collect();
+    let last = original.len() - 1;
+
+    let secret = argv::take_secret(last).unwrap();
+    assert_eq!(secret.as_os_str(), original[last]);
+    assert_eq!(format!("{:?}", secret), "SecretArg(..)");
+
+    let cmdline = fs::read("/proc/self/cmdline").unwrap();
+    let zeros = vec![0; original[last].len() + 1];
+    assert!(cmdline.ends_with(&zeros));
+
+    assert!(argv::verify());
+
+    let args: Vec<&std::ffi::OsStr> = argv::iter().collect();
+    assert_eq!(args.len(), original.len());
+    assert!(args[last].is_empty());
+    assert_eq!(args[..last], original[..last]);
+
+    assert!(argv::take_secret(0).is_err());
+}
@@ -0,0 +1,28 @@
+#![cfg(all(
+    feature = "snapshot",
+    target_os = "linux",
+    target_env = "gnu",
+    not(feature = "zero-alloc"),
+    not(feature = "force-fallback"),
+    not(miri),
+))]
+
+use std::ffi::OsStr;
+use std::ptr;
+
+// Kept as the only test in this binary because it overwrites the original
+// argument memory.
+#[test]
+fn test_snapshot() {
+    let before: Vec<&OsStr> = argv::iter().collect();
+
+    // Without the snapshot, the title could no longer be set at this point.
+    argv::title::set("snapshot").unwrap();
+
+    // Like a C library's own setproctitle.
+    let (start, len) = argv::raw_region().unwrap();
+    unsafe { ptr::write_bytes(start, b'x', len - 1) };
+
+    assert_eq!(argv::iter().collect::<Vec<_>>(), before);
+    assert!(argv::verify());
+}
@@ -0,0 +1,31 @@
+use argv::source::Source;
+
+#[test]
+#[cfg_attr(miri, ignore)] // reads procfs
+fn test_set_order() {
+    assert_eq!(argv::source::order(), argv::source::DEFAULT_ORDER);
+    argv::source::set_order(&[Source::Procfs, Source::Std]).unwrap();
+    assert_eq!(argv::source::order(), [Source::Procfs, Source::Std]);
+
+    let args: Vec<_> = argv::iter().collect();
+    assert_eq!(args, std::env::args_os().collect::<Vec<_>>());
+    let expected = if cfg!(any(target_os = "linux", target_os = "android")) {
+        Source::Procfs
+    } else {
+        Source::Std
+    };
+    assert_eq!(argv::source::used(), Some(expected));
+    assert!(argv::source::set_order(&[Source::Std]).is_err());
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(miri, ignore)] // reads procfs
+fn test_iter_with() {
+    let expected: Vec<_> = std::env::args_os().collect();
+    let procfs: Vec<_> = argv::iter_with(Source::Procfs).collect();
+    assert_eq!(procfs, expected);
+    let std: Vec<_> = argv::iter_with(Source::Std).collect();
+    assert_eq!(std, expected);
+    assert_eq!(argv::iter_with(Source::Custom).len(), 0);
+}
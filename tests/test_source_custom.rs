@@ -0,0 +1,21 @@
+use argv::source::{Capture, Source};
+use std::ffi::OsStr;
+
+struct Simulator;
+
+impl Capture for Simulator {
+    fn capture(&self) -> Vec<&'static OsStr> {
+        vec![OsStr::new("firmware"), OsStr::new("--board=sim")]
+    }
+}
+
+#[test]
+fn test_register() {
+    argv::source::register(&Simulator).unwrap();
+    assert!(argv::source::register(&Simulator).is_err());
+
+    let args: Vec<_> = argv::iter().collect();
+    assert_eq!(args, ["firmware", "--board=sim"]);
+    assert_eq!(argv::source::used(), Some(Source::Custom));
+    assert_eq!(Simulator.capture(), args);
+}
@@ -0,0 +1,13 @@
+#![cfg(all(target_os = "linux", target_env = "gnu"))]
+
+use argv::source::Source;
+
+#[test]
+#[cfg_attr(miri, ignore)] // no __libc_stack_end
+fn test_libc() {
+    argv::source::set_order(&[Source::Libc]).unwrap();
+
+    let args: Vec<_> = argv::iter().collect();
+    assert_eq!(args, std::env::args_os().collect::<Vec<_>>());
+    assert_eq!(argv::source::used(), Some(Source::Libc));
+}
@@ -0,0 +1,25 @@
+use std::io::{self, Write};
+
+fn main() {
+    if argv::test::is_child() {
+        let mut stdout = io::stdout().lock();
+        for arg in argv::iter() {
+            writeln!(stdout, "{}", arg.to_string_lossy()).unwrap();
+        }
+        return;
+    }
+
+    if cfg!(miri) {
+        return;
+    }
+
+    let output = argv::test::spawn_self(&["prog", "", "two words", "--flag=ü"]);
+    io::stderr().lock().write_all(&output.stderr).unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let args: Vec<&str> = stdout.lines().collect();
+    assert_eq!(args[1..], ["", "two words", "--flag=ü"]);
+    if cfg!(unix) {
+        assert_eq!(args[0], "prog");
+    }
+}
@@ -0,0 +1,75 @@
+#![cfg(all(
+    target_os = "linux",
+    target_env = "gnu",
+    not(feature = "zero-alloc"),
+    not(feature = "force-fallback"),
+    not(miri),
+))]
+
+use std::ffi::OsString;
+use std::fs;
+
+// Kept as the only test in this binary because the title can only be set
+// before anything else in the process calls argv::iter().
+#[test]
+fn test_set() {
+    let original: Vec<OsString> = std::env::args_os().collect();
+    let mut joined = OsString::new();
+    for (i, arg) in original.iter().enumerate() {
+        if i > 0 {
+            joined.push(" ");
+        }
+        joined.push(arg);
+    }
+    assert_eq!(argv::title::get().unwrap(), joined);
+
+    argv::title::set("argv-test: title").unwrap();
+
+    let cmdline = fs::read("/proc/self/cmdline").unwrap();
+    assert!(cmdline.starts_with(b"argv-test: title\0"));
+    assert!(cmdline[b"argv-test: title".len()..]
+        .iter()
+        .all(|&b| b == b'\0'));
+    assert_eq!(argv::title::get().unwrap(), "argv-test: title");
+    assert!(argv::verify());
+
+    // Longer than the original command line, so it only fits by reusing the
+    // memory of the environment.
+    let region = argv::stack_region().unwrap();
+    let original_len: usize = original.iter().map(|arg| arg.len() + 1).sum();
+    let space = region.end as usize - region.start as usize;
+    let long_title = "x".repeat((original_len + 64).min(space - 1));
+    argv::title::set(&long_title).unwrap();
+    let cmdline = fs::read("/proc/self/cmdline").unwrap();
+    assert!(cmdline.starts_with(long_title.as_bytes()));
+    assert_eq!(argv::title::get().unwrap(), *long_title);
+    assert!(std::env::var_os("PATH").is_some());
+
+    // Too long for the original memory. Displayed in full from the heap if
+    // the process has CAP_SYS_RESOURCE, otherwise truncated.
+    let huge_title = "y".repeat(space + 100);
+    argv::title::set(&huge_title).unwrap();
+    let title = argv::title::get().unwrap().into_string().unwrap();
+    assert!(title.len() == huge_title.len() || title.len() == space - 1);
+    assert!(huge_title.starts_with(&title));
+    let cmdline = fs::read("/proc/self/cmdline").unwrap();
+    assert!(cmdline.starts_with(title.as_bytes()));
+
+    argv::title::restore().unwrap();
+    assert_eq!(argv::title::get().unwrap(), joined);
+    let args: Vec<OsString> = std::env::args_os().collect();
+    assert_eq!(args, original);
+
+    // Scrubbing after restoring the original command line hides the argument
+    // from /proc but not from argv::iter().
+    let last = original.len() - 1;
+    argv::scrub(last).unwrap();
+    let cmdline = fs::read("/proc/self/cmdline").unwrap();
+    let stars = "*".repeat(original[last].len());
+    assert!(cmdline.ends_with(format!("{}\0", stars).as_bytes()));
+    assert!(argv::scrub(original.len()).is_err());
+
+    let args: Vec<OsString> = argv::iter().map(OsString::from).collect();
+    assert_eq!(args, original);
+    assert_eq!(argv::program_invocation_name(), Some(&*original[0]));
+}
@@ -0,0 +1,57 @@
+use std::env;
+use std::process::{self, Command};
+
+const GRANDCHILD: &str = "ARGV_TEST_GRANDCHILD";
+
+fn inject(id: usize) -> Vec<String> {
+    (0..id).map(|i| format!("--worker-arg={}", i)).collect()
+}
+
+fn main() {
+    if env::var_os(GRANDCHILD).is_some() {
+        let inherited = argv::worker::id().is_some() || argv::worker::injected().len() > 0;
+        process::exit(if inherited { 1 } else { 0 });
+    }
+
+    if let Some(id) = argv::worker::id() {
+        let injected: Vec<_> = argv::worker::injected()
+            .map(|arg| arg.to_str().unwrap())
+            .collect();
+        if injected != inject(id) {
+            process::exit(1);
+        }
+        let removed = cfg!(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            windows,
+        ));
+        if !removed {
+            process::exit(0);
+        }
+        // Removed before main, without waiting for the accessors.
+        if env::var_os("ARGV_WORKER_ID").is_some() {
+            process::exit(1);
+        }
+        // A process started by the worker is not a worker itself.
+        let status = Command::new(env::current_exe().unwrap())
+            .env(GRANDCHILD, "1")
+            .status()
+            .unwrap();
+        process::exit(if status.success() { 0 } else { 1 });
+    }
+
+    assert_eq!(argv::worker::injected().len(), 0);
+    if cfg!(miri) {
+        return;
+    }
+
+    let workers = argv::worker::spawn(3, inject).unwrap();
+    assert_eq!(workers.len(), 3);
+    for mut worker in workers {
+        assert!(worker.wait().unwrap().success());
+    }
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "zero-alloc")]
+
+use argv::zero_alloc::CheckedAllocator;
+use std::alloc::System;
+
+#[global_allocator]
+static ALLOCATOR: CheckedAllocator<System> = CheckedAllocator(System);
+
+#[test]
+fn test_zero_alloc() {
+    let expected: Vec<_> = std::env::args_os().collect();
+
+    // Any allocation inside these calls aborts the test binary.
+    assert_eq!(argv::iter().count(), expected.len());
+    assert_eq!(argv::raw_iter().count(), argv::iter().count());
+    assert!(argv::verify());
+    let _ = argv::program();
+    let _ = argv::is_secure_exec();
+
+    // Scrubbing requires a copy of the arguments, which the snapshot feature
+    // already made before main.
+    let snapshot = cfg!(all(
+        feature = "snapshot",
+        target_os = "linux",
+        not(target_env = "musl"),
+        not(feature = "force-fallback"),
+        not(miri),
+    ));
+    if snapshot {
+        argv::scrub(0).unwrap();
+    } else {
+        let err = argv::scrub(0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    }
+}